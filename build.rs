@@ -0,0 +1,55 @@
+//! Generates a test for every sample case in `data/sample/NN`, which each
+//! day's test module pulls in with `include!` next to its `check_case`.
+
+use std::{collections::HashMap, env, fmt::Write, fs, path::Path};
+
+fn main() {
+    let samples = Path::new("data/sample");
+    let out_dir = env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed={}", samples.display());
+
+    let mut days: Vec<_> = fs::read_dir(samples)
+        .unwrap()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|name| Some((name.parse::<u8>().ok()?, name)))
+        .collect();
+    days.sort();
+
+    for (day, dir) in days {
+        let mut names: Vec<String> = fs::read_dir(samples.join(&dir))
+            .unwrap()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let stem = path.file_stem()?.to_str()?;
+                (path.extension()? == "txt").then(|| stem.to_owned())
+            })
+            .collect();
+        names.sort();
+
+        let mut tests = String::new();
+        let mut idents = HashMap::new();
+
+        for name in names {
+            let ident: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+
+            if let Some(other) = idents.insert(ident.clone(), name.clone()) {
+                panic!(
+                    "data/sample/{dir}/{other}.txt and data/sample/{dir}/{name}.txt \
+                     would both become the test sample_{ident}, rename one of them"
+                );
+            }
+
+            writeln!(
+                tests,
+                "#[test]\nfn sample_{ident}() {{\n    \
+                 check_case(&aoc_2023::input::Case::sample({day}, {name:?}));\n}}\n"
+            )
+            .unwrap();
+        }
+
+        fs::write(Path::new(&out_dir).join(format!("samples_{dir}.rs")), tests).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use aoc_2023::input::{input_path, read_input};
+
 fn solve_part_1(input: &str) -> u64 {
     todo!();
 }
@@ -7,27 +9,28 @@ fn solve_part_2(input: &str) -> u64 {
 }
 
 fn main() {
-    let input = include_str!("../../data/input/day_input.txt");
+    let input = read_input(&input_path(day_number));
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    println!("Part 1: {}", solve_part_1(&input));
+    println!("Part 2: {}", solve_part_2(&input));
 }
 
 #[cfg(test)]
 mod day_test {
     use crate::{solve_part_1, solve_part_2};
+    use aoc_2023::input::Case;
 
-    const SAMPLE: &str = include_str!("../../data/sample/day_input.txt");
-
-    #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 0);
+    fn check_case(case: &Case) {
+        case.check(1, || solve_part_1(&case.input));
+        case.check(2, || solve_part_2(&case.input));
     }
 
+    include!(concat!(env!("OUT_DIR"), "/samples_day_input.rs"));
+
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 0);
+    fn golden() {
+        if let Some(case) = Case::golden(day_number) {
+            check_case(&case);
+        }
     }
 }
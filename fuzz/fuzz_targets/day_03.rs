@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The day's solution, with its `fuzz_input` entry point enabled by cargo-fuzz
+#[path = "../../src/bin/03.rs"]
+#[allow(dead_code)]
+mod day03;
+
+fuzz_target!(|data: &[u8]| day03::fuzz_input(data));
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The day's solution, with its `fuzz_input` entry point enabled by cargo-fuzz
+#[path = "../../src/bin/04.rs"]
+#[allow(dead_code)]
+mod day04;
+
+fuzz_target!(|data: &[u8]| day04::fuzz_input(data));
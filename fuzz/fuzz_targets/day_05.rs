@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The day's solution, with its `fuzz_input` entry point enabled by cargo-fuzz
+#[path = "../../src/bin/05.rs"]
+#[allow(dead_code)]
+mod day05;
+
+fuzz_target!(|data: &[u8]| day05::fuzz_input(data));
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// The day's solution, with its `fuzz_input` entry point enabled by cargo-fuzz
+#[path = "../../src/bin/07.rs"]
+#[allow(dead_code)]
+mod day07;
+
+fuzz_target!(|data: &[u8]| day07::fuzz_input(data));
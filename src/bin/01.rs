@@ -1,93 +1,804 @@
-use std::collections::HashMap;
-
-fn solve_part_1(input: &str) -> u64 {
-    input
-        .lines()
-        .map(|line| {
-            let numbers = line
-                .chars()
-                .filter(char::is_ascii_digit)
-                .collect::<String>();
-            let mut result = String::new();
-            result.push(numbers.chars().next().unwrap());
-            result.push(numbers.chars().last().unwrap());
-            result.parse::<u64>().unwrap()
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader},
+    ops::Range,
+    path::PathBuf,
+    str::FromStr,
+};
+
+const USAGE: &str = "01 [--input <path> | --stream <path> [--values]] [--vocabulary <path>] \
+                     [--ignore-case] [--missing-digits skip|zero|error]";
+
+const DIGITS: [(&str, u64); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+#[derive(Default)]
+struct Vocabulary {
+    words: Vec<(String, u64)>,
+    ignore_case: bool,
+}
+
+impl Vocabulary {
+    fn english() -> Self {
+        include_str!("../../data/vocabulary/en.txt")
+            .parse()
+            .expect("Bundled vocabulary should be valid")
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum VocabularyParseErr {
+    MissingValue { line: usize },
+    InvalidValue { line: usize, value: String },
+}
+
+impl fmt::Display for VocabularyParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VocabularyParseErr::MissingValue { line } => {
+                write!(f, "line {line}: expected `word value`")
+            }
+            VocabularyParseErr::InvalidValue { line, value } => {
+                write!(f, "line {line}: invalid value {value:?}")
+            }
+        }
+    }
+}
+
+impl FromStr for Vocabulary {
+    type Err = VocabularyParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (word, value) = line
+                .rsplit_once(char::is_whitespace)
+                .ok_or(VocabularyParseErr::MissingValue { line: i + 1 })?;
+
+            let value = value
+                .parse::<u64>()
+                .map_err(|_| VocabularyParseErr::InvalidValue {
+                    line: i + 1,
+                    value: value.to_owned(),
+                })?;
+
+            words.push((word.trim_end().to_owned(), value));
+        }
+
+        Ok(Self {
+            words,
+            ignore_case: false,
         })
-        .sum::<u64>()
+    }
 }
 
-fn word_to_num(line: &str, num_map: &HashMap<&str, char>) -> String {
-    let lower_char_bound = 3;
-    let upper_char_bound = 5;
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Match {
+    start: usize,
+    end: usize,
+    value: u64,
+}
 
-    let mut result = String::new();
+// Aho-Corasick automaton with the failure links folded into a full
+// transition table, so scanning is a single table lookup per byte.
+struct Automaton {
+    transitions: Vec<[usize; 256]>,
+    // Length and value of the longest pattern ending in each state.
+    outputs: Vec<Option<(usize, u64)>>,
+    max_length: usize,
+}
+
+impl Automaton {
+    fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = (P, u64)>,
+        P: IntoIterator<Item = u8>,
+    {
+        let mut transitions = vec![[0; 256]];
+        let mut outputs = vec![None];
+        let mut max_length = 0;
+
+        for (pattern, value) in patterns {
+            let mut state = 0;
+            let mut length = 0;
+
+            for b in pattern {
+                let b = usize::from(b);
+
+                if transitions[state][b] == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(None);
+                    transitions[state][b] = transitions.len() - 1;
+                }
+
+                state = transitions[state][b];
+                length += 1;
+            }
+
+            if length > 0 {
+                outputs[state] = Some((length, value));
+                max_length = usize::max(max_length, length);
+            }
+        }
+
+        let mut fail = vec![0; transitions.len()];
+        let mut queue: Vec<usize> = transitions[0].iter().copied().filter(|&e| e != 0).collect();
+        let mut i = 0;
+
+        while i < queue.len() {
+            let state = queue[i];
+            i += 1;
 
-    for (i, c) in line.chars().enumerate() {
-        if c.is_ascii_digit() {
-            result.push(c);
-            continue;
+            let fail_transitions = transitions[fail[state]];
+
+            for (next, fail_next) in transitions[state].iter_mut().zip(fail_transitions) {
+                if *next == 0 {
+                    *next = fail_next;
+                } else {
+                    fail[*next] = fail_next;
+                    outputs[*next] = outputs[*next].or(outputs[fail_next]);
+                    queue.push(*next);
+                }
+            }
         }
 
-        for j in lower_char_bound..=upper_char_bound {
-            if i + j <= line.len() {
-                if let Some(num) = num_map.get(&line[i..i + j]) {
-                    result.push(*num);
+        Self {
+            transitions,
+            outputs,
+            max_length,
+        }
+    }
+
+    // Returns the offset, length and value of the match starting earliest in
+    // `haystack`, preferring the longest one on ties, and stops as soon as no
+    // later match could start before it.
+    fn find(&self, haystack: impl Iterator<Item = u8>) -> Option<(usize, usize, u64)> {
+        let mut state = 0;
+        let mut best: Option<(usize, usize, u64)> = None;
+
+        for (i, b) in haystack.enumerate() {
+            if let Some((start, _, _)) = best {
+                if i >= start + self.max_length {
+                    break;
                 }
             }
+
+            state = self.transitions[state][usize::from(b)];
+
+            if let Some((length, value)) = self.outputs[state] {
+                let start = i + 1 - length;
+
+                if best.is_none_or(|(best_start, _, _)| start <= best_start) {
+                    best = Some((start, length, value));
+                }
+            }
+        }
+
+        best
+    }
+}
+
+fn leading_digit(mut value: u64) -> u64 {
+    while value >= 10 {
+        value /= 10;
+    }
+
+    value
+}
+
+// Unicode lowercasing one char at a time, so words and lines fold alike.
+fn fold_case(s: &str) -> String {
+    s.chars().flat_map(char::to_lowercase).collect()
+}
+
+fn fold_byte(b: u8, lowercase: bool) -> u8 {
+    if lowercase {
+        b.to_ascii_lowercase()
+    } else {
+        b
+    }
+}
+
+// A line as the automatons see it. `spans` holds, for every lowercased byte,
+// the bytes of the line it came from, so that matches point into the line.
+enum Folded<'a> {
+    InPlace {
+        line: &'a [u8],
+        lowercase: bool,
+    },
+    Lowercased {
+        bytes: Vec<u8>,
+        spans: Vec<Range<usize>>,
+    },
+}
+
+impl Folded<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Folded::InPlace { line, .. } => line.len(),
+            Folded::Lowercased { bytes, .. } => bytes.len(),
         }
     }
 
-    result
+    fn to_match(&self, start: usize, end: usize, value: u64) -> Match {
+        let (start, end) = match self {
+            Folded::InPlace { .. } => (start, end),
+            Folded::Lowercased { spans, .. } => (spans[start].start, spans[end - 1].end),
+        };
+
+        Match { start, end, value }
+    }
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let num_map: HashMap<&str, char> = HashMap::from([
-        ("one", '1'),
-        ("two", '2'),
-        ("three", '3'),
-        ("four", '4'),
-        ("five", '5'),
-        ("six", '6'),
-        ("seven", '7'),
-        ("eight", '8'),
-        ("nine", '9'),
-    ]);
+struct DigitMatcher {
+    forward: Automaton,
+    backward: Automaton,
+    ignore_case: bool,
+}
 
-    input
-        .lines()
-        .map(|line| {
-            let numbers = word_to_num(line, &num_map);
-            let mut result = String::new();
-            result.push(numbers.chars().next().unwrap());
-            result.push(numbers.chars().last().unwrap());
-            result.parse::<u64>().unwrap()
+impl DigitMatcher {
+    fn new(vocabulary: &Vocabulary) -> Self {
+        let ignore_case = vocabulary.ignore_case;
+        let patterns: Vec<(String, u64)> = DIGITS
+            .iter()
+            .map(|&(word, value)| (word.to_owned(), value))
+            .chain(vocabulary.words.iter().cloned())
+            .map(|(word, value)| {
+                if ignore_case {
+                    (fold_case(&word), value)
+                } else {
+                    (word, value)
+                }
+            })
+            .collect();
+
+        Self {
+            forward: Automaton::new(patterns.iter().map(|(word, value)| (word.bytes(), *value))),
+            backward: Automaton::new(
+                patterns
+                    .iter()
+                    .map(|(word, value)| (word.bytes().rev(), *value)),
+            ),
+            ignore_case,
+        }
+    }
+
+    // ASCII lines, and all lines when case matters, are scanned in place.
+    // Other lines are lowercased up front, keeping bytes that are not UTF-8.
+    fn fold<'a>(&self, line: &'a [u8]) -> Folded<'a> {
+        if !self.ignore_case || line.is_ascii() {
+            return Folded::InPlace {
+                line,
+                lowercase: self.ignore_case,
+            };
+        }
+
+        let mut bytes = Vec::new();
+        let mut spans = Vec::new();
+        let mut offset = 0;
+
+        for chunk in line.utf8_chunks() {
+            for (i, c) in chunk.valid().char_indices() {
+                for lower in c.to_lowercase() {
+                    bytes.extend_from_slice(lower.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+
+                let span = offset + i..offset + i + c.len_utf8();
+                spans.resize(bytes.len(), span);
+            }
+
+            offset += chunk.valid().len();
+
+            for &b in chunk.invalid() {
+                bytes.push(b);
+                spans.push(offset..offset + 1);
+                offset += 1;
+            }
+        }
+
+        Folded::Lowercased { bytes, spans }
+    }
+
+    fn first(&self, line: &Folded) -> Option<Match> {
+        let found = match line {
+            Folded::InPlace { line, lowercase } => self
+                .forward
+                .find(line.iter().map(|&b| fold_byte(b, *lowercase))),
+            Folded::Lowercased { bytes, .. } => self.forward.find(bytes.iter().copied()),
+        };
+
+        found.map(|(start, length, value)| line.to_match(start, start + length, value))
+    }
+
+    fn last(&self, line: &Folded) -> Option<Match> {
+        let found = match line {
+            Folded::InPlace { line, lowercase } => self
+                .backward
+                .find(line.iter().rev().map(|&b| fold_byte(b, *lowercase))),
+            Folded::Lowercased { bytes, .. } => self.backward.find(bytes.iter().rev().copied()),
+        };
+
+        found.map(|(start, length, value)| {
+            let end = line.len() - start;
+            line.to_match(end - length, end, value)
         })
-        .sum::<u64>()
+    }
+
+    // A word with a multi-digit value reads like the number written out, so
+    // `ten` has 1 as its first digit and 0 as its last.
+    fn calibration_value(&self, line: &[u8]) -> Option<u64> {
+        let line = self.fold(line);
+        Some(leading_digit(self.first(&line)?.value) * 10 + self.last(&line)?.value % 10)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MissingDigitPolicy {
+    Skip,
+    Zero,
+    Error,
+}
+
+impl FromStr for MissingDigitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(MissingDigitPolicy::Skip),
+            "zero" => Ok(MissingDigitPolicy::Zero),
+            "error" => Ok(MissingDigitPolicy::Error),
+            _ => Err(format!("Unknown missing digit policy {s:?}")),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct MissingDigitErr {
+    line: usize,
+}
+
+impl fmt::Display for MissingDigitErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: no digit found", self.line)
+    }
+}
+
+#[derive(Debug)]
+enum CalibrationErr {
+    Io(io::Error),
+    MissingDigit(MissingDigitErr),
+}
+
+impl From<io::Error> for CalibrationErr {
+    fn from(err: io::Error) -> Self {
+        CalibrationErr::Io(err)
+    }
+}
+
+impl From<MissingDigitErr> for CalibrationErr {
+    fn from(err: MissingDigitErr) -> Self {
+        CalibrationErr::MissingDigit(err)
+    }
+}
+
+impl fmt::Display for CalibrationErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationErr::Io(err) => write!(f, "{err}"),
+            CalibrationErr::MissingDigit(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+// How many of the lines without a digit `Calibration` remembers, so that its
+// size stays the same however many there are.
+const MISSING_SAMPLE: usize = 10;
+
+#[derive(Default, PartialEq, Eq, Debug)]
+struct Calibration {
+    sum: u64,
+    // Lines that had no digit and were skipped or counted as zero, and the
+    // first `MISSING_SAMPLE` of their line numbers.
+    missing: usize,
+    missing_lines: Vec<usize>,
+}
+
+impl Calibration {
+    fn add(
+        &mut self,
+        value: Result<u64, MissingDigitErr>,
+        policy: MissingDigitPolicy,
+    ) -> Result<(), MissingDigitErr> {
+        match (value, policy) {
+            (Ok(value), _) => self.sum += value,
+            (Err(err), MissingDigitPolicy::Error) => return Err(err),
+            (Err(err), _) => {
+                self.missing += 1;
+
+                if self.missing_lines.len() < MISSING_SAMPLE {
+                    self.missing_lines.push(err.line);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn print_summary(&self, part: u8, policy: MissingDigitPolicy) {
+        if self.missing == 0 {
+            return;
+        }
+
+        let action = match policy {
+            MissingDigitPolicy::Zero => "counted as zero",
+            _ => "skipped",
+        };
+
+        let mut lines: Vec<String> = self.missing_lines.iter().map(ToString::to_string).collect();
+
+        if self.missing > lines.len() {
+            lines.push(String::from("..."));
+        }
+
+        eprintln!(
+            "Part {part}: {} lines without digits {action}: {}",
+            self.missing,
+            lines.join(", ")
+        );
+    }
+}
+
+// Reads one line at a time into a reused buffer, so memory use does not grow
+// with the input. `on_line` sees every line's value and the running totals.
+fn read_calibration<R>(
+    mut reader: R,
+    matcher: &DigitMatcher,
+    policy: MissingDigitPolicy,
+    mut on_line: impl FnMut(usize, Option<u64>, &Calibration),
+) -> Result<Calibration, CalibrationErr>
+where
+    R: BufRead,
+{
+    let mut calibration = Calibration::default();
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    while reader.read_until(b'\n', &mut buffer)? != 0 {
+        line_number += 1;
+
+        let line = buffer.strip_suffix(b"\n").unwrap_or(&buffer);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let value = matcher.calibration_value(line);
+
+        calibration.add(value.ok_or(MissingDigitErr { line: line_number }), policy)?;
+        on_line(line_number, value, &calibration);
+
+        buffer.clear();
+    }
+
+    Ok(calibration)
+}
+
+fn get_calibration(
+    input: &str,
+    matcher: &DigitMatcher,
+    policy: MissingDigitPolicy,
+) -> Result<Calibration, CalibrationErr> {
+    read_calibration(input.as_bytes(), matcher, policy, |_, _, _| {})
+}
+
+fn solve_part_1(input: &str, policy: MissingDigitPolicy) -> Result<Calibration, CalibrationErr> {
+    get_calibration(input, &DigitMatcher::new(&Vocabulary::default()), policy)
+}
+
+fn solve_part_2(
+    input: &str,
+    vocabulary: &Vocabulary,
+    policy: MissingDigitPolicy,
+) -> Result<Calibration, CalibrationErr> {
+    get_calibration(input, &DigitMatcher::new(vocabulary), policy)
+}
+
+fn print_result(part: u8, result: Result<Calibration, CalibrationErr>, policy: MissingDigitPolicy) {
+    match result {
+        Ok(calibration) => {
+            println!("Part {part}: {}", calibration.sum);
+            calibration.print_summary(part, policy);
+        }
+        Err(err) => eprintln!("Part {part}: {err}"),
+    }
+}
+
+fn stream_calibration(
+    path: &str,
+    matcher: &DigitMatcher,
+    policy: MissingDigitPolicy,
+    print_values: bool,
+) -> Result<Calibration, CalibrationErr> {
+    let reader = BufReader::new(File::open(path)?);
+
+    read_calibration(reader, matcher, policy, |line, value, calibration| {
+        if print_values {
+            match value {
+                Some(value) => println!("{line}: {value} (sum {})", calibration.sum),
+                None => println!("{line}: - (sum {})", calibration.sum),
+            }
+        }
+    })
+}
+
+// Entry point for the `day_01` fuzz target. The input is matched with the
+// English vocabulary, or with itself when it parses as a vocabulary.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    let mut vocabulary = std::str::from_utf8(data)
+        .ok()
+        .and_then(|e| e.parse().ok())
+        .unwrap_or_else(Vocabulary::english);
+    vocabulary.ignore_case = data.len().is_multiple_of(2);
+    let matcher = DigitMatcher::new(&vocabulary);
+
+    for policy in [
+        MissingDigitPolicy::Skip,
+        MissingDigitPolicy::Zero,
+        MissingDigitPolicy::Error,
+    ] {
+        let _ = read_calibration(data, &matcher, policy, |_, _, _| {});
+    }
 }
 
 fn main() {
-    let input = include_str!("../../data/input/01.txt");
+    let mut path = input_path(1);
+
+    let mut vocabulary = None;
+    let mut ignore_case = false;
+    let mut policy = MissingDigitPolicy::Error;
+    let mut stream = None;
+    let mut print_values = false;
+
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--vocabulary" => {
+                    let path = args.value(&arg, "a vocabulary file path")?;
+                    let words = fs::read_to_string(&path)
+                        .map_err(|err| UsageError::invalid(&arg, &path, err))?;
+
+                    vocabulary = Some(
+                        words
+                            .parse::<Vocabulary>()
+                            .map_err(|err| UsageError::invalid(&arg, &path, err))?,
+                    );
+                }
+                "--ignore-case" => ignore_case = true,
+                "--missing-digits" => policy = args.parse(&arg, "skip, zero or error")?,
+                "--stream" => stream = Some(args.value(&arg, "a calibration file path")?),
+                "--values" => print_values = true,
+                "--input" => path = PathBuf::from(args.value(&arg, "a calibration file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+        Ok(())
+    });
+
+    let mut vocabulary = vocabulary.unwrap_or_else(Vocabulary::english);
+    vocabulary.ignore_case = ignore_case;
+
+    if let Some(path) = stream {
+        let matchers = [
+            DigitMatcher::new(&Vocabulary::default()),
+            DigitMatcher::new(&vocabulary),
+        ];
+
+        for (part, matcher) in (1..).zip(&matchers) {
+            let result = stream_calibration(&path, matcher, policy, print_values);
+            print_result(part, result, policy);
+        }
+
+        return;
+    }
+
+    let input = read_input(&path);
+
+    print_result(1, solve_part_1(&input, policy), policy);
+    print_result(2, solve_part_2(&input, &vocabulary, policy), policy);
 }
 
 #[cfg(test)]
 mod day01_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_input, read_calibration, solve_part_1, solve_part_2, Calibration, CalibrationErr,
+        DigitMatcher, Match, MissingDigitErr, MissingDigitPolicy, Vocabulary, VocabularyParseErr,
+        MISSING_SAMPLE,
+    };
+    use aoc_2023::input::Case;
+
+    const SAMPLE: &str = include_str!("../../data/sample/01/sample_2.txt");
+
+    fn check_case(case: &Case) {
+        case.check(1, || {
+            solve_part_1(&case.input, MissingDigitPolicy::Error)
+                .unwrap()
+                .sum
+        });
+        case.check(2, || {
+            solve_part_2(
+                &case.input,
+                &Vocabulary::english(),
+                MissingDigitPolicy::Error,
+            )
+            .unwrap()
+            .sum
+        });
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/samples_01.rs"));
+
+    #[test]
+    fn golden() {
+        if let Some(case) = Case::golden(1) {
+            check_case(&case);
+        }
+    }
+
+    #[test]
+    fn overlapping_and_non_ascii() {
+        let matcher = DigitMatcher::new(&Vocabulary::english());
+
+        assert_eq!(matcher.calibration_value(b"eightwo"), Some(82));
+        assert_eq!(matcher.calibration_value(b"a0b5"), Some(5));
+        assert_eq!(matcher.calibration_value("ünë3twoneß".as_bytes()), Some(31));
+        assert_eq!(
+            matcher.last(&matcher.fold("éseven".as_bytes())),
+            Some(Match {
+                start: 2,
+                end: 7,
+                value: 7
+            })
+        );
+        assert_eq!(matcher.calibration_value("ünë".as_bytes()), None);
+    }
+
+    #[test]
+    fn custom_vocabulary() {
+        let mut vocabulary: Vocabulary = include_str!("../../data/vocabulary/roman.txt")
+            .parse()
+            .unwrap();
+        vocabulary.ignore_case = true;
+        let matcher = DigitMatcher::new(&vocabulary);
+
+        assert_eq!(matcher.calibration_value(b"xVIIIyIv"), Some(84));
+        assert_eq!(matcher.calibration_value(b"0 and VII"), Some(7));
+
+        // 'İ' lowercases to two chars, matches still point into the line
+        let line = "xİV".as_bytes();
+        assert_eq!(
+            matcher.first(&matcher.fold(line)),
+            Some(Match {
+                start: 1,
+                end: 3,
+                value: 1
+            })
+        );
+        assert_eq!(
+            matcher.last(&matcher.fold(line)),
+            Some(Match {
+                start: 3,
+                end: 4,
+                value: 5
+            })
+        );
+
+        let german: Vocabulary = include_str!("../../data/vocabulary/de.txt")
+            .parse()
+            .unwrap();
+        let matcher = DigitMatcher::new(&german);
+
+        assert_eq!(matcher.calibration_value(b"dreiundvierzig"), Some(34));
+        assert_eq!(matcher.calibration_value("FÜNF x DREI".as_bytes()), None);
 
-    const SAMPLE1: &str = include_str!("../../data/sample/01_1.txt");
-    const SAMPLE2: &str = include_str!("../../data/sample/01_2.txt");
+        let german = Vocabulary {
+            ignore_case: true,
+            ..german
+        };
+        let matcher = DigitMatcher::new(&german);
+
+        assert_eq!(
+            matcher.calibration_value("FÜNF x DREI".as_bytes()),
+            Some(53)
+        );
+        assert_eq!(matcher.calibration_value(b"F\xc3\x9cnf\xff"), Some(55));
+
+        let err = "one 1\ntwo\n".parse::<Vocabulary>().err();
+        assert_eq!(err, Some(VocabularyParseErr::MissingValue { line: 2 }));
+
+        let err = "# tens\nten x\n".parse::<Vocabulary>().err();
+        assert_eq!(
+            err,
+            Some(VocabularyParseErr::InvalidValue {
+                line: 2,
+                value: String::from("x")
+            })
+        );
+
+        let tens: Vocabulary = "ten 10\ntwenty 20\ntwelve 12\n".parse().unwrap();
+        let matcher = DigitMatcher::new(&tens);
+
+        assert_eq!(matcher.calibration_value(b"xtenx"), Some(10));
+        assert_eq!(matcher.calibration_value(b"twenty3twelve"), Some(22));
+        assert_eq!(matcher.calibration_value(b"5twelve"), Some(52));
+    }
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE1);
-        assert_eq!(result, 142);
+    fn missing_digits() {
+        let input = "a1b2\nnothing\n\n3\n";
+
+        let result = solve_part_1(input, MissingDigitPolicy::Error);
+        assert!(matches!(
+            result,
+            Err(CalibrationErr::MissingDigit(MissingDigitErr { line: 2 }))
+        ));
+
+        let expected = Calibration {
+            sum: 45,
+            missing: 2,
+            missing_lines: vec![2, 3],
+        };
+        let result = solve_part_1(input, MissingDigitPolicy::Skip).unwrap();
+        assert_eq!(result, expected);
+
+        let result = solve_part_1(input, MissingDigitPolicy::Zero).unwrap();
+        assert_eq!(result.sum, 45);
+
+        let result = solve_part_1(&"x\n".repeat(1000), MissingDigitPolicy::Skip).unwrap();
+        assert_eq!(result.missing, 1000);
+        assert_eq!(
+            result.missing_lines,
+            (1..=MISSING_SAMPLE).collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE2);
-        assert_eq!(result, 281);
+    fn streaming() {
+        let matcher = DigitMatcher::new(&Vocabulary::english());
+        let mut values = Vec::new();
+
+        let result = read_calibration(
+            SAMPLE.replace('\n', "\r\n").as_bytes(),
+            &matcher,
+            MissingDigitPolicy::Error,
+            |line, value, calibration| values.push((line, value, calibration.sum)),
+        )
+        .unwrap();
+
+        assert_eq!(result.sum, 281);
+        assert_eq!(values[0], (1, Some(29), 29));
+        assert_eq!(values[1], (2, Some(83), 112));
+        assert_eq!(values.len(), 7);
     }
+
+    aoc_2023::fuzz_test!(1, 1000, fuzz_input);
 }
@@ -1,41 +1,178 @@
-use std::str::FromStr;
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::PathBuf,
+    str::FromStr,
+};
 
-#[derive(Debug)]
-struct ParsingTurnError;
+const USAGE: &str = "02 [--input <path>] [--bag <bag>]... [--bags <path>] [--report] \
+                     [--sum-duplicates]";
 
-#[derive(Debug)]
+const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum DuplicateColors {
+    #[default]
+    Reject,
+    Sum,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum TurnErrorKind {
+    MissingAmount,
+    InvalidAmount(String),
+    MissingColor,
+    UnexpectedToken(String),
+    DuplicateColor(String),
+    AmountOverflow(String),
+}
+
+impl fmt::Display for TurnErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnErrorKind::MissingAmount => write!(f, "missing cube amount"),
+            TurnErrorKind::InvalidAmount(amount) => write!(f, "invalid cube amount {amount:?}"),
+            TurnErrorKind::MissingColor => write!(f, "missing cube color"),
+            TurnErrorKind::UnexpectedToken(token) => write!(f, "unexpected {token:?}"),
+            TurnErrorKind::DuplicateColor(color) => write!(f, "duplicate color {color:?}"),
+            TurnErrorKind::AmountOverflow(color) => write!(f, "too many {color:?} cubes"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct ParsingTurnError {
+    column: usize,
+    kind: TurnErrorKind,
+}
+
+impl fmt::Display for ParsingTurnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.kind)
+    }
+}
+
+// Byte offset of `part` within `s`, where `part` is a subslice of `s`.
+fn offset_of(s: &str, part: &str) -> usize {
+    part.as_ptr() as usize - s.as_ptr() as usize
+}
+
+#[derive(Default, PartialEq, Eq, Clone, Debug)]
 struct Turn {
-    red: u64,
-    green: u64,
-    blue: u64,
+    cubes: BTreeMap<String, u64>,
 }
 
+// A bag holds the same color to count map as a turn.
+type Bag = Turn;
+
 impl Turn {
-    fn new(red: u64, green: u64, blue: u64) -> Self {
-        Self { red, green, blue }
+    fn get(&self, color: &str) -> u64 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    fn contains(&self, other: &Turn) -> bool {
+        other
+            .cubes
+            .iter()
+            .all(|(color, amount)| *amount <= self.get(color))
+    }
+
+    // Multiplies the amounts of all colors in `self` or in `colors`, so one
+    // of `colors` that `self` lacks counts as 0. `None` when the result does
+    // not fit in 64 bits, as for `total`.
+    fn power(&self, colors: &[&str]) -> Option<u64> {
+        colors
+            .iter()
+            .filter(|color| !self.cubes.contains_key(**color))
+            .map(|_| 0)
+            .chain(self.cubes.values().copied())
+            .try_fold(1_u64, |power, amount| power.checked_mul(amount))
+    }
+
+    fn total(&self) -> Option<u64> {
+        self.cubes
+            .values()
+            .try_fold(0_u64, |total, &amount| total.checked_add(amount))
+    }
+
+    fn extend_to(&mut self, other: &Turn) {
+        for (color, amount) in &other.cubes {
+            let max_amount = self.cubes.entry(color.clone()).or_insert(0);
+            *max_amount = u64::max(*max_amount, *amount);
+        }
     }
 }
 
-impl FromStr for Turn {
-    type Err = ParsingTurnError;
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, amount)) in self.cubes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut turn = Turn::new(0, 0, 0);
+            write!(f, "{amount} {color}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Turn {
+    fn parse(s: &str, duplicates: DuplicateColors) -> Result<Self, ParsingTurnError> {
+        let mut turn = Turn::default();
+
+        for cube in s.split(',') {
+            let error = |column: usize, kind: TurnErrorKind| ParsingTurnError {
+                column: column + 1,
+                kind,
+            };
+
+            let mut it = cube.split_whitespace();
 
-        for e in s.split(',') {
-            let mut it = e.split(' ');
-            it.next();
             let amount = it
                 .next()
-                .expect("Should be the amount of colored cube")
-                .parse::<u64>()
-                .expect("Should be the number");
-
-            match it.next().expect("Should be a color") {
-                "blue" => turn.blue = amount,
-                "green" => turn.green = amount,
-                "red" => turn.red = amount,
-                _ => panic!("Not a valid color"),
+                .ok_or_else(|| error(offset_of(s, cube), TurnErrorKind::MissingAmount))?;
+
+            let amount_value = amount.parse::<u64>().map_err(|_| {
+                error(
+                    offset_of(s, amount),
+                    TurnErrorKind::InvalidAmount(amount.to_owned()),
+                )
+            })?;
+
+            let color = it.next().ok_or_else(|| {
+                error(offset_of(s, cube) + cube.len(), TurnErrorKind::MissingColor)
+            })?;
+
+            if let Some(token) = it.next() {
+                return Err(error(
+                    offset_of(s, token),
+                    TurnErrorKind::UnexpectedToken(token.to_owned()),
+                ));
+            }
+
+            match turn.cubes.get_mut(color) {
+                Some(total) if duplicates == DuplicateColors::Sum => {
+                    *total = total.checked_add(amount_value).ok_or_else(|| {
+                        error(
+                            offset_of(s, amount),
+                            TurnErrorKind::AmountOverflow(color.to_owned()),
+                        )
+                    })?;
+                }
+                Some(_) => {
+                    return Err(error(
+                        offset_of(s, color),
+                        TurnErrorKind::DuplicateColor(color.to_owned()),
+                    ))
+                }
+                None => {
+                    turn.cubes.insert(color.to_owned(), amount_value);
+                }
             }
         }
 
@@ -43,80 +180,456 @@ impl FromStr for Turn {
     }
 }
 
-fn solve_part_1(input: &str) -> u64 {
-    let mut sum: u64 = 0;
+impl FromStr for Turn {
+    type Err = ParsingTurnError;
 
-    for (i, line) in input.lines().enumerate() {
-        let result = line
-            .split(':')
-            .nth(1)
-            .expect("Should be the game's turns")
-            .split(';')
-            .map(|e| e.parse::<Turn>().expect("Expected a turn format"))
-            .find(|e| e.red > 12 || e.green > 13 || e.blue > 14);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Turn::parse(s, DuplicateColors::Reject)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum GameErrorKind {
+    MissingHeader,
+    InvalidId(String),
+    InvalidTurn(TurnErrorKind),
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct ParsingGameError {
+    line: usize,
+    column: usize,
+    kind: GameErrorKind,
+}
+
+impl fmt::Display for ParsingGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+
+        match &self.kind {
+            GameErrorKind::MissingHeader => write!(f, "expected `Game <id>:`"),
+            GameErrorKind::InvalidId(id) => write!(f, "invalid game id {id:?}"),
+            GameErrorKind::InvalidTurn(kind) => write!(f, "{kind}"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum GameError {
+    Parsing(ParsingGameError),
+    Overflow,
+}
+
+impl From<ParsingGameError> for GameError {
+    fn from(err: ParsingGameError) -> Self {
+        GameError::Parsing(err)
+    }
+}
 
-        if result.is_none() {
-            sum +=
-                u64::try_from(i).expect("Number should not be larger than 32 bit unsigned int") + 1;
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Parsing(err) => write!(f, "{err}"),
+            GameError::Overflow => write!(f, "result does not fit in 64 bits"),
         }
     }
+}
 
-    sum
+#[derive(Debug)]
+struct Game {
+    id: u64,
+    turns: Vec<Turn>,
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let mut sum: u64 = 0;
+impl Game {
+    fn is_possible(&self, bag: &Bag) -> bool {
+        self.turns.iter().all(|turn| bag.contains(turn))
+    }
+
+    fn minimum_bag(&self) -> Bag {
+        let mut bag = Bag::default();
 
-    for line in input.lines() {
-        let mut max_amount = (0, 0, 0);
+        for turn in &self.turns {
+            bag.extend_to(turn);
+        }
 
-        line.split(':')
-            .nth(1)
-            .expect("Should be the game's turns")
+        bag
+    }
+}
+
+impl Game {
+    fn parse(s: &str, line: usize, duplicates: DuplicateColors) -> Result<Self, ParsingGameError> {
+        let error = |column: usize, kind: GameErrorKind| ParsingGameError { line, column, kind };
+
+        let (header, turns) = s
+            .split_once(':')
+            .ok_or_else(|| error(1, GameErrorKind::MissingHeader))?;
+
+        let mut it = header.split_whitespace();
+
+        if it.next() != Some("Game") {
+            return Err(error(1, GameErrorKind::MissingHeader));
+        }
+
+        let id = match (it.next(), it.next()) {
+            (Some(id), None) => id.parse::<u64>().map_err(|_| {
+                error(
+                    offset_of(s, id) + 1,
+                    GameErrorKind::InvalidId(id.to_owned()),
+                )
+            })?,
+            _ => return Err(error(1, GameErrorKind::MissingHeader)),
+        };
+
+        let turns = turns
             .split(';')
-            .map(|e| e.parse::<Turn>().expect("Expected a turn format"))
-            .for_each(|turn| {
-                if turn.red > max_amount.0 {
-                    max_amount.0 = turn.red;
-                }
+            .map(|turn| {
+                Turn::parse(turn, duplicates).map_err(|err| {
+                    error(
+                        offset_of(s, turn) + err.column,
+                        GameErrorKind::InvalidTurn(err.kind),
+                    )
+                })
+            })
+            .collect::<Result<Vec<Turn>, ParsingGameError>>()?;
 
-                if turn.green > max_amount.1 {
-                    max_amount.1 = turn.green;
-                }
+        Ok(Game { id, turns })
+    }
+}
 
-                if turn.blue > max_amount.2 {
-                    max_amount.2 = turn.blue;
-                }
-            });
+impl FromStr for Game {
+    type Err = ParsingGameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Game::parse(s, 1, DuplicateColors::Reject)
+    }
+}
+
+fn parse_games(input: &str, duplicates: DuplicateColors) -> Result<Vec<Game>, ParsingGameError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Game::parse(line, i + 1, duplicates))
+        .collect()
+}
+
+// For every bag, the IDs of the games that could have been played with it.
+fn get_feasible_games<'a>(games: &[Game], bags: &'a [Bag]) -> Vec<(&'a Bag, Vec<u64>)> {
+    bags.iter()
+        .map(|bag| {
+            let ids = games
+                .iter()
+                .filter(|game| game.is_possible(bag))
+                .map(|game| game.id)
+                .collect();
+
+            (bag, ids)
+        })
+        .collect()
+}
+
+// The bag with the fewest cubes that makes every game possible.
+fn get_smallest_bag(games: &[Game]) -> Bag {
+    let mut bag = Bag::default();
+
+    for game in games {
+        bag.extend_to(&game.minimum_bag());
+    }
+
+    bag
+}
+
+// Every color of `bags`, for the power of games played with them.
+fn get_all_colors(bags: &[Bag]) -> Vec<&str> {
+    let colors: BTreeSet<&str> = bags
+        .iter()
+        .flat_map(|bag| bag.cubes.keys().map(String::as_str))
+        .collect();
+
+    colors.into_iter().collect()
+}
+
+fn format_amount(amount: Option<u64>) -> String {
+    amount.map_or_else(|| String::from("too large"), |e| e.to_string())
+}
 
-        sum += max_amount.0 * max_amount.1 * max_amount.2;
+fn print_report(games: &[Game], bags: &[Bag]) {
+    println!("Minimum bags:");
+
+    let colors = get_all_colors(bags);
+
+    for game in games {
+        let bag = game.minimum_bag();
+        println!(
+            "  Game {}: {bag} (power {})",
+            game.id,
+            format_amount(bag.power(&colors))
+        );
+    }
+
+    println!("Feasible games:");
+
+    for (bag, ids) in get_feasible_games(games, bags) {
+        let ids: Vec<String> = ids.iter().map(ToString::to_string).collect();
+        println!("  {bag}: {}", ids.join(", "));
     }
 
-    sum
+    let bag = get_smallest_bag(games);
+    println!(
+        "Smallest bag for all games: {bag} ({} cubes)",
+        format_amount(bag.total())
+    );
+}
+
+fn solve_part_1(input: &str, bag: &Bag, duplicates: DuplicateColors) -> Result<u64, GameError> {
+    parse_games(input, duplicates)?
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .try_fold(0_u64, |sum, game| sum.checked_add(game.id))
+        .ok_or(GameError::Overflow)
+}
+
+fn solve_part_2(
+    input: &str,
+    colors: &[&str],
+    duplicates: DuplicateColors,
+) -> Result<u64, GameError> {
+    parse_games(input, duplicates)?
+        .iter()
+        .try_fold(0_u64, |sum, game| {
+            sum.checked_add(game.minimum_bag().power(colors)?)
+        })
+        .ok_or(GameError::Overflow)
+}
+
+// Entry point for the `day_02` fuzz target.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let bags = [
+        DEFAULT_BAG.parse().unwrap(),
+        input.parse().unwrap_or_default(),
+    ];
+
+    for duplicates in [DuplicateColors::Reject, DuplicateColors::Sum] {
+        if let Ok(games) = parse_games(input, duplicates) {
+            get_feasible_games(&games, &bags);
+            get_smallest_bag(&games).total();
+        }
+
+        let _ = solve_part_1(input, &bags[0], duplicates);
+        let _ = solve_part_2(input, &get_all_colors(&bags[1..]), duplicates);
+    }
 }
 
 fn main() {
-    let input = include_str!("../../data/input/02.txt");
+    let mut path = input_path(2);
+
+    let mut bags: Vec<Bag> = Vec::new();
+    let mut report = false;
+    let mut duplicates = DuplicateColors::Reject;
+
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bag" => bags.push(args.parse(&arg, "a bag such as \"12 red, 13 green\"")?),
+                "--bags" => {
+                    let path = args.value(&arg, "a bag file path")?;
+                    let content = fs::read_to_string(&path)
+                        .map_err(|err| UsageError::invalid(&arg, &path, err))?;
+
+                    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                        let bag = line.parse::<Bag>().map_err(|err| {
+                            UsageError::invalid(&arg, &path, format!("invalid bag {line:?}: {err}"))
+                        })?;
+                        bags.push(bag);
+                    }
+                }
+                "--report" => report = true,
+                "--sum-duplicates" => duplicates = DuplicateColors::Sum,
+                "--input" => path = PathBuf::from(args.value(&arg, "a game record file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(())
+    });
+
+    let input = read_input(&path);
+
+    if bags.is_empty() {
+        bags.push(DEFAULT_BAG.parse().unwrap());
+    }
+
+    if report {
+        match parse_games(&input, duplicates) {
+            Ok(games) => print_report(&games, &bags),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+
+    for bag in &bags {
+        match solve_part_1(&input, bag, duplicates) {
+            Ok(result) => println!("Part 1 ({bag}): {result}"),
+            Err(err) => eprintln!("Part 1 ({bag}): {err}"),
+        }
+    }
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::print_result(2, solve_part_2(&input, &get_all_colors(&bags), duplicates));
 }
 
 #[cfg(test)]
 mod day02_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_input, get_all_colors, get_feasible_games, get_smallest_bag, parse_games,
+        solve_part_1, solve_part_2, Bag, DuplicateColors, GameErrorKind, ParsingGameError, Turn,
+        TurnErrorKind, DEFAULT_BAG,
+    };
+    use aoc_2023::input::Case;
+
+    const SAMPLE: &str = include_str!("../../data/sample/02/sample.txt");
+
+    fn check_case(case: &Case) {
+        case.check(1, || {
+            solve_part_1(
+                &case.input,
+                &DEFAULT_BAG.parse().unwrap(),
+                DuplicateColors::Reject,
+            )
+            .unwrap()
+        });
+        case.check(2, || {
+            solve_part_2(
+                &case.input,
+                &["red", "green", "blue"],
+                DuplicateColors::Reject,
+            )
+            .unwrap()
+        });
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/samples_02.rs"));
+
+    #[test]
+    fn golden() {
+        if let Some(case) = Case::golden(2) {
+            check_case(&case);
+        }
+    }
+
+    #[test]
+    fn shuffled_ids() {
+        let mut games: Vec<&str> = SAMPLE
+            .lines()
+            .filter(|e| !e.starts_with("Game 2:"))
+            .collect();
+        games.reverse();
+        let input = games.join("\n").replace("Game 5:", "Game 50:");
 
-    const SAMPLE: &str = include_str!("../../data/sample/02.txt");
+        let result = solve_part_1(
+            &input,
+            &DEFAULT_BAG.parse().unwrap(),
+            DuplicateColors::Reject,
+        )
+        .unwrap();
+        assert_eq!(result, 51);
+    }
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 8);
+    fn custom_bags() {
+        let input = "Game 1: 2 yellow, 1 red; 3 purple\nGame 2: 1 yellow\nGame 3: 4 red";
+
+        let bag: Bag = "3 purple, 2 yellow, 1 red".parse().unwrap();
+        assert_eq!(solve_part_1(input, &bag, DuplicateColors::Reject), Ok(3));
+
+        let bag: Bag = "5 red, 1 yellow".parse().unwrap();
+        assert_eq!(solve_part_1(input, &bag, DuplicateColors::Reject), Ok(5));
+
+        let bags: Vec<Bag> = ["3 purple, 2 yellow", "1 red, 5 yellow"]
+            .iter()
+            .map(|e| e.parse().unwrap())
+            .collect();
+        let colors = get_all_colors(&bags);
+        assert_eq!(colors, ["purple", "red", "yellow"]);
+        assert_eq!(solve_part_2(input, &colors, DuplicateColors::Reject), Ok(6));
+
+        assert_eq!(
+            solve_part_2(input, &["yellow"], DuplicateColors::Reject),
+            Ok(6 + 1)
+        );
+
+        // Without any bag color, each game only counts the colors it shows
+        assert_eq!(
+            solve_part_2(input, &[], DuplicateColors::Reject),
+            Ok(6 + 1 + 4)
+        );
     }
 
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 2286);
+    fn missing_colors() {
+        let input = "Game 1: 3 red; 2 blue\nGame 2: 1 red, 2 green, 3 blue";
+        let bags: [Bag; 1] = [DEFAULT_BAG.parse().unwrap()];
+        let colors = get_all_colors(&bags);
+
+        assert_eq!(solve_part_2(input, &colors, DuplicateColors::Reject), Ok(6));
     }
+
+    #[test]
+    fn bag_queries() {
+        let games = parse_games(SAMPLE, DuplicateColors::Reject).unwrap();
+
+        assert_eq!(
+            games[2].minimum_bag().to_string(),
+            "6 blue, 13 green, 20 red"
+        );
+
+        let bags: Vec<Bag> = [DEFAULT_BAG, "20 red, 13 green, 6 blue"]
+            .iter()
+            .map(|e| e.parse().unwrap())
+            .collect();
+        let feasible: Vec<Vec<u64>> = get_feasible_games(&games, &bags)
+            .into_iter()
+            .map(|(_, ids)| ids)
+            .collect();
+        assert_eq!(feasible, vec![vec![1, 2, 5], vec![1, 2, 3, 5]]);
+
+        let smallest = get_smallest_bag(&games);
+        assert_eq!(smallest.to_string(), "15 blue, 13 green, 20 red");
+        assert_eq!(smallest.total(), Some(48));
+        assert!(games.iter().all(|game| game.is_possible(&smallest)));
+    }
+
+    #[test]
+    fn turn_whitespace_and_errors() {
+        let turn: Turn = "3 blue,  4\tred ,1 green".parse().unwrap();
+        assert_eq!(turn.to_string(), "3 blue, 1 green, 4 red");
+
+        let result = "3 blue, 4 red, 2 blue".parse::<Turn>().map(|_| ());
+        assert_eq!(
+            result.unwrap_err().kind,
+            TurnErrorKind::DuplicateColor(String::from("blue"))
+        );
+
+        let turn = Turn::parse("3 blue, 4 red, 2 blue", DuplicateColors::Sum).unwrap();
+        assert_eq!(turn.get("blue"), 5);
+
+        let input = "Game 1: 3 blue\nGame  2 :1 red;  x green";
+        let result = parse_games(input, DuplicateColors::Reject).map(|_| ());
+        assert_eq!(
+            result,
+            Err(ParsingGameError {
+                line: 2,
+                column: 18,
+                kind: GameErrorKind::InvalidTurn(TurnErrorKind::InvalidAmount(String::from("x"))),
+            })
+        );
+
+        let result = parse_games("Game 1: 3 blue,", DuplicateColors::Reject).map(|_| ());
+        assert_eq!(result.unwrap_err().column, 16);
+    }
+
+    aoc_2023::fuzz_test!(2, 100, fuzz_input);
 }
@@ -1,201 +1,711 @@
-use std::collections::HashMap;
+use aoc_2023::{
+    cli::{self, UsageError},
+    grid::{Grid, Location},
+    input::{input_path, read_input},
+};
+use std::{collections::HashMap, fmt, fs, ops::Range, path::PathBuf};
 
-fn is_part_number(x: usize, y: usize, length: usize, schema: &Vec<Vec<char>>) -> bool {
-    let start_x: usize = isize::max(
-        isize::try_from(x).unwrap() - isize::try_from(length).unwrap() - 1,
-        0,
-    )
-    .try_into()
-    .unwrap();
+const USAGE: &str = "03 [--input <path>] [--symbols <chars> [--arity <n>|<n>+]] [--render] \
+                     [--html <path>] [--empty-chars <chars>] [--symbol-chars <chars>]";
 
-    for i in -1..=1 {
-        if (y == 0 && i == -1) || (y == schema.len() - 1 && i == 1) {
-            continue;
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Number {
+    row: usize,
+    start: usize,
+    end: usize,
+    value: u64,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Symbol {
+    location: Location,
+    symbol: char,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Token {
+    Number(Number),
+    Symbol(Symbol),
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct CharClasses {
+    empty: Vec<char>,
+    // When `None`, anything that is not a digit, empty, whitespace or a
+    // control character counts as a symbol.
+    symbols: Option<Vec<char>>,
+}
+
+impl Default for CharClasses {
+    fn default() -> Self {
+        Self {
+            empty: vec!['.'],
+            symbols: None,
         }
+    }
+}
 
-        let found = schema[usize::try_from(isize::try_from(y).unwrap() + i).unwrap()]
-            [start_x..usize::min(x + 1, schema[y].len() - 1)]
-            .iter()
-            .any(|e| !e.is_ascii_digit() && *e != '.');
+impl CharClasses {
+    fn is_symbol(&self, c: char) -> bool {
+        if c.is_ascii_digit() {
+            return false;
+        }
 
-        if found {
-            return true;
+        match &self.symbols {
+            Some(symbols) => symbols.contains(&c),
+            None => !self.empty.contains(&c) && !c.is_whitespace() && !c.is_control(),
         }
     }
+}
 
-    false
+#[derive(PartialEq, Eq, Debug)]
+enum SchemaError {
+    NumberTooLarge(Location),
+    Overflow,
 }
 
-fn solve_part_1(input: &str) -> u64 {
-    let schema: Vec<Vec<char>> = input
-        .lines()
-        .map(|e| e.chars().collect::<Vec<char>>())
-        .collect();
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::NumberTooLarge(Location { x, y }) => write!(
+                f,
+                "number at line {}, column {} does not fit in 64 bits",
+                y + 1,
+                x + 1
+            ),
+            SchemaError::Overflow => write!(f, "result does not fit in 64 bits"),
+        }
+    }
+}
 
-    let mut number = String::new();
-    let mut result = 0;
+struct Schematic {
+    grid: Grid<char>,
+    classes: CharClasses,
+    tokens: Vec<Token>,
+}
 
-    for (y, line) in schema.iter().enumerate() {
-        for (x, symbol) in line.iter().enumerate() {
-            match *symbol {
-                '0'..='9' => {
-                    number.push(*symbol);
+fn tokenize_row(y: usize, row: &[char], classes: &CharClasses) -> Result<Vec<Token>, SchemaError> {
+    let mut tokens = Vec::new();
+    let mut x = 0;
 
-                    if x == line.len() - 1 {
-                        if is_part_number(x, y, number.len(), &schema) {
-                            result += number.parse::<u64>().unwrap();
-                        }
+    while x < row.len() {
+        if let Some(digit) = row[x].to_digit(10) {
+            let start = x;
+            let mut value = u64::from(digit);
+            x += 1;
 
-                        number.clear();
-                    }
-                }
-                _ => {
-                    if !number.is_empty() {
-                        if is_part_number(x, y, number.len(), &schema) {
-                            result += number.parse::<u64>().unwrap();
-                        }
+            while let Some(digit) = row.get(x).and_then(|c| c.to_digit(10)) {
+                value = value
+                    .checked_mul(10)
+                    .and_then(|e| e.checked_add(u64::from(digit)))
+                    .ok_or(SchemaError::NumberTooLarge(Location::new(start, y)))?;
+                x += 1;
+            }
 
-                        number.clear();
-                    }
+            tokens.push(Token::Number(Number {
+                row: y,
+                start,
+                end: x,
+                value,
+            }));
+        } else {
+            if classes.is_symbol(row[x]) {
+                tokens.push(Token::Symbol(Symbol {
+                    location: Location::new(x, y),
+                    symbol: row[x],
+                }));
+            }
+
+            x += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+// Numbers and symbols in reading order. Number spans are `start..end` columns.
+fn tokenize(schema: &Schematic) -> impl Iterator<Item = Token> + '_ {
+    schema.tokens.iter().copied()
+}
+
+fn get_numbers(schema: &Schematic) -> impl Iterator<Item = Number> + '_ {
+    tokenize(schema).filter_map(|token| match token {
+        Token::Number(number) => Some(number),
+        Token::Symbol(_) => None,
+    })
+}
+
+// Columns around a number, from the one before its first digit to the one
+// after its last digit, clamped to the schematic. They are checked on the
+// number's row and the rows directly above and below it.
+fn get_window(number: &Number, schema: &Schematic) -> Range<usize> {
+    number.start.saturating_sub(1)..usize::min(number.end + 1, schema.grid.width())
+}
+
+fn get_window_rows<'a>(
+    number: &Number,
+    schema: &'a Schematic,
+) -> impl Iterator<Item = (usize, &'a [char])> {
+    (number.row.saturating_sub(1)..=number.row + 1)
+        .filter_map(|row| Some((row, schema.grid.row(row)?)))
+}
+
+fn is_part_number(number: &Number, schema: &Schematic) -> bool {
+    let window = get_window(number, schema);
+
+    get_window_rows(number, schema).any(|(_, row)| {
+        row[window.clone()]
+            .iter()
+            .any(|&c| schema.classes.is_symbol(c))
+    })
+}
+
+// Accepts `\n`, `\r\n` and lone `\r` line endings, and pads rows shorter
+// than the longest one with empty cells.
+fn parse_schema(input: &str, classes: &CharClasses) -> Result<Schematic, SchemaError> {
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    let fill = *classes
+        .empty
+        .first()
+        .expect("Should have at least one empty character");
+
+    let grid = Grid::from_rows_padded(input.lines().map(str::chars), fill);
+    let mut tokens = Vec::new();
+
+    for (y, row) in grid.rows().enumerate() {
+        tokens.extend(tokenize_row(y, row, classes)?);
+    }
+
+    Ok(Schematic {
+        grid,
+        classes: classes.clone(),
+        tokens,
+    })
+}
+
+fn solve_part_1(input: &str, classes: &CharClasses) -> Result<u64, SchemaError> {
+    let schema = parse_schema(input, classes)?;
+
+    let sum = get_numbers(&schema)
+        .filter(|number| is_part_number(number, &schema))
+        .try_fold(0_u64, |sum, number| sum.checked_add(number.value));
+
+    sum.ok_or(SchemaError::Overflow)
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+struct SymbolMatch {
+    symbol: Symbol,
+    numbers: Vec<Number>,
+}
+
+impl SymbolMatch {
+    fn product(&self) -> Option<u64> {
+        self.numbers
+            .iter()
+            .try_fold(1_u64, |product, number| product.checked_mul(number.value))
+    }
+}
+
+fn sum_products(matches: &[SymbolMatch]) -> Result<u64, SchemaError> {
+    matches
+        .iter()
+        .try_fold(0_u64, |sum, e| sum.checked_add(e.product()?))
+        .ok_or(SchemaError::Overflow)
+}
+
+// Every symbol accepted by `is_wanted`, in reading order, together with the
+// numbers adjacent to it, keeping only those whose count satisfies `arity`.
+fn query_symbols(
+    schema: &Schematic,
+    is_wanted: impl Fn(char) -> bool,
+    arity: impl Fn(usize) -> bool,
+) -> Vec<SymbolMatch> {
+    let mut matches = Vec::new();
+    let mut numbers = Vec::new();
+
+    for token in tokenize(schema) {
+        match token {
+            Token::Symbol(symbol) if is_wanted(symbol.symbol) => matches.push(SymbolMatch {
+                symbol,
+                numbers: Vec::new(),
+            }),
+            Token::Symbol(_) => {}
+            Token::Number(number) => numbers.push(number),
+        }
+    }
+
+    let index: HashMap<Location, usize> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.symbol.location, i))
+        .collect();
+
+    for number in numbers {
+        let window = get_window(&number, schema);
+
+        for (y, _) in get_window_rows(&number, schema) {
+            for x in window.clone() {
+                if let Some(&i) = index.get(&Location::new(x, y)) {
+                    matches[i].numbers.push(number);
                 }
             }
         }
     }
 
-    result
+    matches.retain(|e| arity(e.numbers.len()));
+    matches
+}
+
+fn solve_part_2(input: &str, classes: &CharClasses) -> Result<u64, SchemaError> {
+    let schema = parse_schema(input, classes)?;
+
+    sum_products(&query_symbols(&schema, |c| c == '*', |arity| arity == 2))
+}
+
+// Accepts an exact count such as `2`, or a minimum such as `2+`.
+fn parse_arity(arity: &str) -> Option<Box<dyn Fn(usize) -> bool>> {
+    match arity.strip_suffix('+') {
+        Some(min) => {
+            let min: usize = min.parse().ok()?;
+            Some(Box::new(move |count| count >= min))
+        }
+        None => {
+            let exact: usize = arity.parse().ok()?;
+            Some(Box::new(move |count| count == exact))
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, Hash, Debug)]
-struct Location {
-    x: usize,
-    y: usize,
+fn print_query(
+    input: &str,
+    classes: &CharClasses,
+    symbols: &str,
+    arity: &dyn Fn(usize) -> bool,
+) -> Result<(), SchemaError> {
+    let schema = parse_schema(input, classes)?;
+    let matches = query_symbols(&schema, |c| symbols.contains(c), arity);
+
+    for e in &matches {
+        let numbers: Vec<String> = e.numbers.iter().map(|n| n.value.to_string()).collect();
+        let Location { x, y } = e.symbol.location;
+
+        println!(
+            "{} at {},{}: {}",
+            e.symbol.symbol,
+            x + 1,
+            y + 1,
+            numbers.join(", ")
+        );
+    }
+
+    println!(
+        "{} matches, sum of products {}",
+        matches.len(),
+        sum_products(&matches)?
+    );
+
+    Ok(())
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Highlight {
+    Empty,
+    PartNumber,
+    Number,
+    Gear,
+    Symbol,
+}
+
+impl Highlight {
+    fn ansi_colour(self) -> Option<&'static str> {
+        match self {
+            Highlight::Empty => None,
+            Highlight::PartNumber => Some("\x1b[1;32m"),
+            Highlight::Number => Some("\x1b[31m"),
+            Highlight::Gear => Some("\x1b[1;33m"),
+            Highlight::Symbol => Some("\x1b[36m"),
+        }
+    }
+
+    fn class(self) -> Option<&'static str> {
+        match self {
+            Highlight::Empty => None,
+            Highlight::PartNumber => Some("part"),
+            Highlight::Number => Some("number"),
+            Highlight::Gear => Some("gear"),
+            Highlight::Symbol => Some("symbol"),
+        }
+    }
 }
 
-impl Location {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
+fn get_highlights(schema: &Schematic) -> Grid<Highlight> {
+    let mut highlights = schema.grid.map(|&c| {
+        if schema.classes.is_symbol(c) {
+            Highlight::Symbol
+        } else {
+            Highlight::Empty
+        }
+    });
+
+    for number in get_numbers(schema) {
+        let highlight = if is_part_number(&number, schema) {
+            Highlight::PartNumber
+        } else {
+            Highlight::Number
+        };
+
+        for x in number.start..number.end {
+            highlights[Location::new(x, number.row)] = highlight;
+        }
     }
+
+    for gear in query_symbols(schema, |c| c == '*', |arity| arity == 2) {
+        highlights[gear.symbol.location] = Highlight::Gear;
+    }
+
+    highlights
 }
 
-type Gear = Vec<u64>;
+// Splits a row into runs of consecutive cells sharing the same highlight.
+fn get_runs(row: &[char], highlights: &[Highlight]) -> Vec<(Highlight, String)> {
+    let mut runs: Vec<(Highlight, String)> = Vec::new();
 
-fn populate_gears(
-    x: usize,
-    y: usize,
-    length: usize,
-    schema: &Vec<Vec<char>>,
-    gears: &mut HashMap<Location, Gear>,
-    number: u64,
-) {
-    let start_x: usize = isize::max(
-        isize::try_from(x).unwrap() - isize::try_from(length).unwrap() - 1,
-        0,
-    )
-    .try_into()
-    .unwrap();
+    for (&c, &highlight) in row.iter().zip(highlights) {
+        match runs.last_mut() {
+            Some((last, text)) if *last == highlight => text.push(c),
+            _ => runs.push((highlight, c.to_string())),
+        }
+    }
+
+    runs
+}
+
+fn render_ansi(schema: &Schematic) -> String {
+    let highlights = get_highlights(schema);
+    let mut output = String::new();
 
-    for i in -1..=1 {
-        if (y == 0 && i == -1) || (y == schema.len() - 1 && i == 1) {
-            continue;
+    for (row, highlight_row) in schema.grid.rows().zip(highlights.rows()) {
+        for (highlight, text) in get_runs(row, highlight_row) {
+            match highlight.ansi_colour() {
+                Some(colour) => output.push_str(&format!("{colour}{text}\x1b[0m")),
+                None => output.push_str(&text),
+            }
         }
 
-        let s = &schema[usize::try_from(isize::try_from(y).unwrap() + i).unwrap()]
-            [start_x..usize::min(x + 1, schema[y].len() - 1)];
-        let pos = s.iter().position(|&e| e == '*');
+        output.push('\n');
+    }
+
+    output
+}
+
+const HTML_STYLE: &str = "body { background: #1e1e1e; color: #808080; }
+.part { color: #4ec94e; font-weight: bold; }
+.number { color: #e05252; }
+.gear { color: #f0c000; font-weight: bold; }
+.symbol { color: #4fc1e9; }";
 
-        if let Some(gear_x) = pos {
-            if let Some(gear) = gears.get_mut(&Location::new(
-                gear_x + start_x,
-                usize::try_from(isize::try_from(y).unwrap() + i).unwrap(),
-            )) {
-                gear.push(number);
-            } else {
-                gears.insert(
-                    Location::new(
-                        gear_x + start_x,
-                        usize::try_from(isize::try_from(y).unwrap() + i).unwrap(),
-                    ),
-                    vec![number],
-                );
+fn render_html(schema: &Schematic) -> String {
+    let highlights = get_highlights(schema);
+    let mut output = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Schematic</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n<pre>\n"
+    );
+
+    for (row, highlight_row) in schema.grid.rows().zip(highlights.rows()) {
+        for (highlight, text) in get_runs(row, highlight_row) {
+            let text = text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+
+            match highlight.class() {
+                Some(class) => output.push_str(&format!("<span class=\"{class}\">{text}</span>")),
+                None => output.push_str(&text),
             }
         }
+
+        output.push('\n');
     }
+
+    output.push_str("</pre>\n</body>\n</html>\n");
+    output
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let schema: Vec<Vec<char>> = input
-        .lines()
-        .map(|e| e.chars().collect::<Vec<char>>())
-        .collect();
+// Entry point for the `day_03` fuzz target. Its first chars also give
+// character classes to try.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
 
-    let mut number = String::new();
-    let mut gears: HashMap<Location, Gear> = HashMap::new();
-
-    for (y, line) in schema.iter().enumerate() {
-        for (x, symbol) in line.iter().enumerate() {
-            match *symbol {
-                '0'..='9' => {
-                    number.push(*symbol);
-
-                    if x == line.len() - 1 {
-                        populate_gears(
-                            x,
-                            y,
-                            number.len(),
-                            &schema,
-                            &mut gears,
-                            number.parse().unwrap(),
-                        );
-
-                        number.clear();
-                    }
+    let custom = fuzz_classes(input);
+
+    for classes in [CharClasses::default()].into_iter().chain(custom) {
+        if let Ok(schema) = parse_schema(input, &classes) {
+            render_html(&schema);
+            let _ = sum_products(&query_symbols(&schema, |_| true, |_| true));
+        }
+
+        let _ = solve_part_1(input, &classes);
+        let _ = solve_part_2(input, &classes);
+    }
+}
+
+// The first char picks how many of the next ones are empty, at least one as
+// for `--empty-chars`, and how many after those are symbols, if any.
+#[cfg(any(test, fuzzing))]
+fn fuzz_classes(input: &str) -> Option<CharClasses> {
+    let mut chars = input.chars();
+    let code = u32::from(chars.next()?) as usize;
+    let empty: Vec<char> = chars.by_ref().take(1 + code % 3).collect();
+    let symbols = match code / 3 % 4 {
+        0 => None,
+        count => Some(chars.take(count).collect()),
+    };
+
+    (!empty.is_empty()).then_some(CharClasses { empty, symbols })
+}
+
+fn main() {
+    let mut path = input_path(3);
+    let mut classes = CharClasses::default();
+
+    let mut symbols = None;
+    let mut render = false;
+    let mut html = None;
+    let mut arity: Box<dyn Fn(usize) -> bool> = Box::new(|_| true);
+
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--symbols" => symbols = Some(args.value(&arg, "a set of symbols")?),
+                "--arity" => {
+                    let expected = "an arity such as 2 or 2+";
+                    let value = args.value(&arg, expected)?;
+                    arity = parse_arity(&value).ok_or_else(|| {
+                        UsageError::invalid(&arg, &value, format!("expected {expected}"))
+                    })?;
                 }
-                _ => {
-                    if !number.is_empty() {
-                        populate_gears(
-                            x,
-                            y,
-                            number.len(),
-                            &schema,
-                            &mut gears,
-                            number.parse().unwrap(),
-                        );
-                        number.clear();
+                "--render" => render = true,
+                "--html" => html = Some(args.value(&arg, "an output file path")?),
+                "--input" => path = PathBuf::from(args.value(&arg, "a schematic file path")?),
+                "--empty-chars" => {
+                    classes.empty = args.value(&arg, "a set of characters")?.chars().collect();
+
+                    if classes.empty.is_empty() {
+                        let reason = "expected at least one empty character";
+                        return Err(UsageError::invalid(&arg, "", reason));
                     }
                 }
+                "--symbol-chars" => {
+                    classes.symbols =
+                        Some(args.value(&arg, "a set of characters")?.chars().collect());
+                }
+                _ => return Err(UsageError::UnknownArgument(arg)),
             }
         }
+
+        Ok(())
+    });
+
+    let input = read_input(&path);
+
+    if render || html.is_some() {
+        let schema = match parse_schema(&input, &classes) {
+            Ok(schema) => schema,
+            Err(err) => return eprintln!("{err}"),
+        };
+
+        if render {
+            print!("{}", render_ansi(&schema));
+        }
+
+        if let Some(path) = html {
+            fs::write(&path, render_html(&schema))
+                .unwrap_or_else(|err| panic!("Could not write {path}: {err}"));
+        }
     }
 
-    gears
-        .values()
-        .filter(|gear| gear.len() == 2)
-        .map(|gear| gear.iter().product::<u64>())
-        .sum()
-}
+    if let Some(symbols) = symbols {
+        if let Err(err) = print_query(&input, &classes, &symbols, &arity) {
+            eprintln!("{err}");
+        }
 
-fn main() {
-    let input = include_str!("../../data/input/03.txt");
+        return;
+    }
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::print_result(1, solve_part_1(&input, &classes));
+    cli::print_result(2, solve_part_2(&input, &classes));
 }
 
 #[cfg(test)]
 mod day03_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_classes, fuzz_input, get_highlights, parse_schema, query_symbols, render_html,
+        solve_part_1, solve_part_2, tokenize, CharClasses, Highlight, Number, SchemaError, Symbol,
+        SymbolMatch, Token,
+    };
+    use aoc_2023::{grid::Location, input::Case};
+
+    const SAMPLE: &str = include_str!("../../data/sample/03/sample.txt");
+
+    fn check_case(case: &Case) {
+        case.check(1, || {
+            solve_part_1(&case.input, &CharClasses::default()).unwrap()
+        });
+        case.check(2, || {
+            solve_part_2(&case.input, &CharClasses::default()).unwrap()
+        });
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/samples_03.rs"));
+
+    #[test]
+    fn golden() {
+        if let Some(case) = Case::golden(3) {
+            check_case(&case);
+        }
+    }
+
+    #[test]
+    fn tokens() {
+        let schema = parse_schema("467..#\n..35*9", &CharClasses::default()).unwrap();
+        let tokens: Vec<Token> = tokenize(&schema).collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(Number {
+                    row: 0,
+                    start: 0,
+                    end: 3,
+                    value: 467
+                }),
+                Token::Symbol(Symbol {
+                    location: Location::new(5, 0),
+                    symbol: '#'
+                }),
+                Token::Number(Number {
+                    row: 1,
+                    start: 2,
+                    end: 4,
+                    value: 35
+                }),
+                Token::Symbol(Symbol {
+                    location: Location::new(4, 1),
+                    symbol: '*'
+                }),
+                Token::Number(Number {
+                    row: 1,
+                    start: 5,
+                    end: 6,
+                    value: 9
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn symbol_queries() {
+        let schema = parse_schema("1.2.3\n.#.#.\n4...5", &CharClasses::default()).unwrap();
 
-    const SAMPLE: &str = include_str!("../../data/sample/03.txt");
+        let matches = query_symbols(&schema, |c| c == '#', |arity| arity == 3);
+        let found: Vec<(Location, Option<u64>)> = matches
+            .iter()
+            .map(|e| (e.symbol.location, e.product()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Location::new(1, 1), Some(8)),
+                (Location::new(3, 1), Some(30))
+            ]
+        );
+
+        let schema = parse_schema(SAMPLE, &CharClasses::default()).unwrap();
+        let total: usize = query_symbols(&schema, |c| "#$+".contains(c), |_| true)
+            .iter()
+            .map(|e| e.numbers.len())
+            .sum();
+        assert_eq!(total, 3);
+
+        let lonely = query_symbols(&schema, |c| c == '*', |arity| arity < 2);
+        assert_eq!(
+            lonely.iter().map(SymbolMatch::product).collect::<Vec<_>>(),
+            vec![Some(617)]
+        );
+    }
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 4361);
+    fn highlights() {
+        let schema = parse_schema("1*2.3\n...&<", &CharClasses::default()).unwrap();
+        let highlights = get_highlights(&schema);
+
+        assert_eq!(
+            highlights.row(0),
+            Some(
+                &[
+                    Highlight::PartNumber,
+                    Highlight::Gear,
+                    Highlight::PartNumber,
+                    Highlight::Empty,
+                    Highlight::PartNumber
+                ][..]
+            )
+        );
+
+        let html = render_html(&schema);
+        assert!(html.contains("<span class=\"gear\">*</span>"));
+        assert!(html.contains("<span class=\"symbol\">&amp;&lt;</span>"));
+
+        let highlights = get_highlights(&parse_schema("1..*", &CharClasses::default()).unwrap());
+        assert_eq!(highlights[Location::new(0, 0)], Highlight::Number);
+        assert_eq!(highlights[Location::new(3, 0)], Highlight::Symbol);
     }
 
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 467835);
+    fn irregular_schematics() {
+        let classes = CharClasses::default();
+
+        assert_eq!(
+            solve_part_1("467..\r\n...*.\r\n..35\r\n", &classes).unwrap(),
+            502
+        );
+        assert_eq!(solve_part_1("467..\r...*.\r..35\r", &classes).unwrap(), 502);
+        assert_eq!(solve_part_2("1\n.*\n..2", &classes).unwrap(), 2);
+
+        let schema = parse_schema("12\n.#..\n", &classes).unwrap();
+        assert_eq!(schema.grid.row(0), Some(&['1', '2', '.', '.'][..]));
+
+        let classes = CharClasses {
+            empty: vec![' ', '_'],
+            symbols: Some(vec!['*', '.']),
+        };
+        assert_eq!(solve_part_1("12#\n___\n3._", &classes).unwrap(), 3);
+        assert_eq!(solve_part_2("12_ \n__* \n  34", &classes).unwrap(), 408);
+
+        let classes = CharClasses::default();
+        let result = parse_schema("...\n.99999999999999999999", &classes).map(|_| ());
+        assert_eq!(
+            result,
+            Err(SchemaError::NumberTooLarge(Location::new(1, 1)))
+        );
+
+        let input = format!("{}*\n{}.", u64::MAX, u64::MAX);
+        assert_eq!(solve_part_1(&input, &classes), Err(SchemaError::Overflow));
+        assert_eq!(solve_part_2(&input, &classes), Err(SchemaError::Overflow));
+    }
+
+    aoc_2023::fuzz_test!(3, 30, fuzz_input);
+
+    #[test]
+    fn fuzz_classes_from_first_chars() {
+        let classes = fuzz_classes("\u{4}#.$x").unwrap();
+        assert_eq!(classes.empty, ['#', '.']);
+        assert_eq!(classes.symbols, Some(vec!['$']));
+
+        assert_eq!(fuzz_classes("\u{0}"), None);
+        assert_eq!(fuzz_classes("\u{0}.x").unwrap().symbols, None);
     }
 }
@@ -1,124 +1,564 @@
-use std::str::FromStr;
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{cmp::Ordering, fmt, path::PathBuf, str::FromStr};
 
-#[derive(Debug)]
-struct ParsingError;
+const USAGE: &str =
+    "04 [--input <path>] [--scoring doubling|linear|fibonacci|table:<points>] [--report]";
 
-struct Card {
-    winning_numbers: Vec<u64>,
-    numbers: Vec<u64>,
+#[derive(PartialEq, Eq, Debug)]
+enum ParsingError {
+    MissingHeader,
+    InvalidId(String),
+    MissingNumbers,
+    InvalidNumber(String),
+    DuplicateNumber(u64),
 }
 
-impl Card {
-    fn new(winning_numbers: Vec<u64>, numbers: Vec<u64>) -> Self {
-        Self {
-            winning_numbers,
-            numbers,
+#[derive(PartialEq, Eq, Debug)]
+enum CardIdError {
+    Duplicate(u64),
+    Missing(u64),
+}
+
+#[derive(Debug)]
+enum CardError {
+    Parsing { line: usize, error: ParsingError },
+    Id(CardIdError),
+    Overflow(u64),
+    PointsOverflow(u64),
+}
+
+impl fmt::Display for CardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardError::Parsing { line, error } => match error {
+                ParsingError::MissingHeader => write!(f, "line {line}: expected `Card <id>:`"),
+                ParsingError::InvalidId(id) => write!(f, "line {line}: invalid card id {id:?}"),
+                ParsingError::MissingNumbers => write!(f, "line {line}: expected `|`"),
+                ParsingError::InvalidNumber(n) => write!(f, "line {line}: invalid number {n:?}"),
+                ParsingError::DuplicateNumber(n) => write!(f, "line {line}: duplicate number {n}"),
+            },
+            CardError::Id(CardIdError::Duplicate(id)) => write!(f, "card {id} appears twice"),
+            CardError::Id(CardIdError::Missing(id)) => write!(f, "card {id} is missing"),
+            CardError::Overflow(id) => write!(f, "copies of card {id} overflow"),
+            CardError::PointsOverflow(id) => write!(f, "points of card {id} overflow"),
         }
     }
+}
 
-    fn get_winning_count(&self) -> u64 {
-        let mut count = 0;
+// Numbers up to this bound are stored in a bitset of at most 1024 words,
+// larger ones in a sorted vector searched by bisection.
+const BITSET_LIMIT: u64 = 1 << 16;
+
+#[derive(PartialEq, Eq, Debug)]
+enum NumberSet {
+    Bits(Vec<u64>),
+    Sorted(Vec<u64>),
+}
+
+impl NumberSet {
+    // Fails with the first number that appears twice.
+    fn new(numbers: &[u64]) -> Result<Self, u64> {
+        let max = numbers.iter().copied().max().unwrap_or(0);
 
-        for win in &self.winning_numbers {
-            if self.numbers.contains(win) {
-                count += 1;
+        if max < BITSET_LIMIT {
+            let mut bits = vec![0_u64; usize::try_from(max / 64).unwrap() + 1];
+
+            for &n in numbers {
+                let (word, bit) = (usize::try_from(n / 64).unwrap(), 1 << (n % 64));
+
+                if bits[word] & bit != 0 {
+                    return Err(n);
+                }
+
+                bits[word] |= bit;
             }
+
+            return Ok(NumberSet::Bits(bits));
         }
 
-        count
-    }
+        let mut sorted = numbers.to_vec();
+        sorted.sort_unstable();
 
-    fn get_points(&self) -> u64 {
-        let count = self.get_winning_count();
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(pair[0]);
+        }
 
-        if count == 0 {
-            return 0;
+        Ok(NumberSet::Sorted(sorted))
+    }
+
+    fn contains(&self, n: u64) -> bool {
+        match self {
+            NumberSet::Bits(bits) => usize::try_from(n / 64)
+                .ok()
+                .and_then(|word| bits.get(word))
+                .is_some_and(|word| word & (1 << (n % 64)) != 0),
+            NumberSet::Sorted(sorted) => sorted.binary_search(&n).is_ok(),
         }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+enum Scoring {
+    // 1, 2, 4, 8, ... points for 1, 2, 3, 4, ... matches.
+    Doubling,
+    // One point per match.
+    Linear,
+    // 1, 2, 3, 5, 8, ... points for 1, 2, 3, 4, 5, ... matches.
+    Fibonacci,
+    // Points indexed by the number of matches, repeating the last entry
+    // for higher counts.
+    Table(Vec<u64>),
+}
+
+impl Scoring {
+    fn get_points(&self, count: u64) -> Option<u64> {
+        match self {
+            Scoring::Table(table) => {
+                let index = usize::try_from(count).unwrap_or(usize::MAX);
+                table.get(index).or(table.last()).copied()
+            }
+            _ if count == 0 => Some(0),
+            Scoring::Doubling => 2_u64.checked_pow(u32::try_from(count - 1).ok()?),
+            Scoring::Linear => Some(count),
+            Scoring::Fibonacci => {
+                let (mut previous, mut points) = (1_u64, 1_u64);
+
+                for _ in 1..count {
+                    (previous, points) = (points, previous.checked_add(points)?);
+                }
 
-        2_u64.pow((count - 1).try_into().unwrap())
+                Some(points)
+            }
+        }
     }
+}
 
-    fn set_card_winning_copies(&self, cards: &mut [u64], id: usize) {
-        let count = self.get_winning_count();
+impl FromStr for Scoring {
+    type Err = String;
 
-        for _ in 0..cards[id] {
-            for card in cards
-                .iter_mut()
-                .take(id + usize::try_from(count).unwrap() + 1)
-                .skip(id + 1)
-            {
-                *card += 1;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "doubling" => Ok(Scoring::Doubling),
+            "linear" => Ok(Scoring::Linear),
+            "fibonacci" => Ok(Scoring::Fibonacci),
+            _ => {
+                let table = s
+                    .strip_prefix("table:")
+                    .ok_or_else(|| format!("Unknown scoring {s:?}"))?;
+
+                table
+                    .split(',')
+                    .map(|e| e.trim().parse::<u64>())
+                    .collect::<Result<Vec<u64>, _>>()
+                    .map(Scoring::Table)
+                    .map_err(|_| format!("Invalid points table {table:?}"))
             }
         }
     }
 }
 
+struct Card {
+    id: u64,
+    winning_numbers: Vec<u64>,
+    numbers: NumberSet,
+}
+
+impl Card {
+    fn new(id: u64, winning_numbers: Vec<u64>, numbers: &[u64]) -> Result<Self, ParsingError> {
+        NumberSet::new(&winning_numbers).map_err(ParsingError::DuplicateNumber)?;
+
+        Ok(Self {
+            id,
+            winning_numbers,
+            numbers: NumberSet::new(numbers).map_err(ParsingError::DuplicateNumber)?,
+        })
+    }
+
+    fn get_winning_count(&self) -> u64 {
+        let count = self
+            .winning_numbers
+            .iter()
+            .filter(|&&win| self.numbers.contains(win))
+            .count();
+
+        u64::try_from(count).unwrap()
+    }
+
+    fn get_points(&self, scoring: &Scoring) -> Result<u64, CardError> {
+        scoring
+            .get_points(self.get_winning_count())
+            .ok_or(CardError::PointsOverflow(self.id))
+    }
+
+    // Every copy of this card wins one copy of each of the following
+    // `count` cards, so they all gain `cards[id]` copies at once.
+    fn set_card_winning_copies(&self, cards: &mut [u128], id: usize) -> Result<(), CardError> {
+        let count = usize::try_from(self.get_winning_count()).unwrap();
+        let copies = cards[id];
+
+        for card in cards.iter_mut().take(id + count + 1).skip(id + 1) {
+            *card = card
+                .checked_add(copies)
+                .ok_or(CardError::Overflow(self.id))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_numbers(s: &str) -> Result<Vec<u64>, ParsingError> {
+    s.split_whitespace()
+        .map(|n| {
+            n.parse::<u64>()
+                .map_err(|_| ParsingError::InvalidNumber(n.to_owned()))
+        })
+        .collect()
+}
+
 impl FromStr for Card {
     type Err = ParsingError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut it = s.split('|').map(|e| {
-            e.split(' ')
-                .filter(|e| !e.is_empty())
-                .map(|n| n.parse::<u64>().unwrap())
-                .collect::<Vec<u64>>()
-        });
+        let (header, numbers) = s.split_once(':').ok_or(ParsingError::MissingHeader)?;
+
+        let mut it = header.split_whitespace();
+
+        if it.next() != Some("Card") {
+            return Err(ParsingError::MissingHeader);
+        }
+
+        let id = it.next().ok_or(ParsingError::MissingHeader)?;
+        let id = id
+            .parse::<u64>()
+            .map_err(|_| ParsingError::InvalidId(id.to_owned()))?;
 
-        Ok(Card::new(it.next().unwrap(), it.next().unwrap()))
+        let (winning_numbers, numbers) = numbers
+            .split_once('|')
+            .ok_or(ParsingError::MissingNumbers)?;
+
+        Card::new(
+            id,
+            parse_numbers(winning_numbers)?,
+            &parse_numbers(numbers)?,
+        )
     }
 }
 
-fn solve_part_1(input: &str) -> u64 {
+fn parse_cards(input: &str) -> Result<Vec<Card>, CardError> {
     input
         .lines()
-        .map(|e| {
-            e.split(':')
-                .nth(1)
-                .unwrap()
-                .parse::<Card>()
-                .unwrap()
-                .get_points()
+        .enumerate()
+        .map(|(i, e)| {
+            e.parse::<Card>()
+                .map_err(|error| CardError::Parsing { line: i + 1, error })
         })
-        .sum()
+        .collect()
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let mut cards: Vec<u64> = vec![1; input.lines().collect::<Vec<&str>>().len()];
+// Orders the cards by ID, which must run from 1 to the number of cards.
+fn sort_cards_by_id(cards: &mut [Card]) -> Result<(), CardIdError> {
+    cards.sort_by_key(|card| card.id);
 
-    input.lines().enumerate().for_each(|(i, e)| {
-        e.split(':')
-            .nth(1)
-            .unwrap()
-            .parse::<Card>()
-            .unwrap()
-            .set_card_winning_copies(&mut cards, i);
-    });
+    let mut expected = 1;
+
+    for card in cards.iter() {
+        match card.id.cmp(&expected) {
+            Ordering::Less => return Err(CardIdError::Duplicate(card.id)),
+            Ordering::Greater => return Err(CardIdError::Missing(expected)),
+            Ordering::Equal => expected += 1,
+        }
+    }
 
-    cards.iter().sum()
+    Ok(())
+}
+
+fn get_total_points(cards: &[Card], scoring: &Scoring) -> Result<u64, CardError> {
+    cards.iter().try_fold(0_u64, |sum, card| {
+        sum.checked_add(card.get_points(scoring)?)
+            .ok_or(CardError::PointsOverflow(card.id))
+    })
+}
+
+// Copies held of each card, in card ID order.
+fn get_copies(cards: &mut [Card]) -> Result<Vec<u128>, CardError> {
+    sort_cards_by_id(cards).map_err(CardError::Id)?;
+
+    let mut copies: Vec<u128> = vec![1; cards.len()];
+
+    for (i, card) in cards.iter().enumerate() {
+        card.set_card_winning_copies(&mut copies, i)?;
+    }
+
+    Ok(copies)
+}
+
+fn solve_part_1(input: &str, scoring: &Scoring) -> Result<u64, CardError> {
+    get_total_points(&parse_cards(input)?, scoring)
+}
+
+fn solve_part_2(input: &str) -> Result<u128, CardError> {
+    let mut cards = parse_cards(input)?;
+    let copies = get_copies(&mut cards)?;
+
+    cards.iter().zip(copies).try_fold(0_u128, |sum, (card, e)| {
+        sum.checked_add(e).ok_or(CardError::Overflow(card.id))
+    })
+}
+
+fn print_report(input: &str, scoring: &Scoring) -> Result<(), CardError> {
+    let mut cards = parse_cards(input)?;
+    let copies = get_copies(&mut cards)?;
+
+    for (card, copies) in cards.iter().zip(copies) {
+        println!(
+            "Card {}: {} matches, {} points, {copies} copies",
+            card.id,
+            card.get_winning_count(),
+            card.get_points(scoring)?
+        );
+    }
+
+    Ok(())
+}
+
+// Entry point for the `day_04` fuzz target. The first line also tries to
+// be a scoring rule.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let custom = input.lines().next().and_then(|e| e.parse().ok());
+
+    for scoring in [Scoring::Doubling, Scoring::Linear, Scoring::Fibonacci]
+        .into_iter()
+        .chain(custom)
+    {
+        let _ = solve_part_1(input, &scoring);
+    }
+
+    let _ = solve_part_2(input);
 }
 
 fn main() {
-    let input = include_str!("../../data/input/04.txt");
+    let mut path = input_path(4);
+
+    let mut scoring = Scoring::Doubling;
+    let mut report = false;
+
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scoring" => {
+                    scoring = args.parse(&arg, "doubling, linear, fibonacci or table:<points>")?;
+                }
+                "--report" => report = true,
+                "--input" => path = PathBuf::from(args.value(&arg, "a card file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(())
+    });
+
+    let input = read_input(&path);
+
+    if report {
+        if let Err(err) = print_report(&input, &scoring) {
+            eprintln!("{err}");
+        }
+    }
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::print_result(1, solve_part_1(&input, &scoring));
+    cli::print_result(2, solve_part_2(&input));
 }
 
 #[cfg(test)]
 mod day04_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_input, get_copies, get_total_points, solve_part_1, solve_part_2, Card, CardError,
+        CardIdError, NumberSet, ParsingError, Scoring, BITSET_LIMIT,
+    };
+    use aoc_2023::{
+        input::Case,
+        prop::{agree, check},
+    };
+
+    const SAMPLE: &str = include_str!("../../data/sample/04/sample.txt");
+
+    fn check_case(case: &Case) {
+        case.check(1, || solve_part_1(&case.input, &Scoring::Doubling).unwrap());
+        case.check(2, || solve_part_2(&case.input).unwrap());
+    }
 
-    const SAMPLE: &str = include_str!("../../data/sample/04.txt");
+    include!(concat!(env!("OUT_DIR"), "/samples_04.rs"));
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 13);
+    fn golden() {
+        if let Some(case) = Case::golden(4) {
+            check_case(&case);
+        }
     }
 
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
+    fn card_ids() {
+        let mut lines: Vec<&str> = SAMPLE.lines().collect();
+        lines.reverse();
+        let result = solve_part_2(&lines.join("\n")).unwrap();
         assert_eq!(result, 30);
+
+        let gap = SAMPLE.replace("Card 3:", "Card 7:");
+        assert!(matches!(
+            solve_part_2(&gap),
+            Err(CardError::Id(CardIdError::Missing(3)))
+        ));
+
+        let duplicate = SAMPLE.replace("Card 3:", "Card 2:");
+        assert!(matches!(
+            solve_part_2(&duplicate),
+            Err(CardError::Id(CardIdError::Duplicate(2)))
+        ));
     }
+
+    #[test]
+    fn copy_overflow() {
+        let card = |id: usize, count: usize| {
+            let numbers: Vec<String> = (1..=count).map(|n| n.to_string()).collect();
+            format!("Card {id}: {0} | {0}", numbers.join(" "))
+        };
+
+        let input: Vec<String> = (1..=128).map(|id| card(id, 128 - id)).collect();
+        let result = solve_part_2(&input.join("\n")).unwrap();
+        assert_eq!(result, u128::MAX);
+
+        let input: Vec<String> = (1..=140).map(|id| card(id, 140 - id)).collect();
+        let result = solve_part_2(&input.join("\n"));
+        assert!(matches!(result, Err(CardError::Overflow(128))));
+
+        // Each card's copies fit, their sum overflows at card 129
+        let input: Vec<String> = (1..=130)
+            .map(|id| card(id, 128_usize.saturating_sub(id)))
+            .collect();
+        let result = solve_part_2(&input.join("\n"));
+        assert!(matches!(result, Err(CardError::Overflow(129))));
+    }
+
+    #[test]
+    fn number_sets() {
+        let numbers = [3, 64, 65, 1000];
+        assert!(matches!(NumberSet::new(&numbers), Ok(NumberSet::Bits(_))));
+
+        let large = [3, 64, u64::MAX, 1 << 40];
+        assert!(matches!(NumberSet::new(&large), Ok(NumberSet::Sorted(_))));
+
+        for set in [NumberSet::new(&numbers), NumberSet::new(&large)] {
+            let set = set.unwrap();
+            assert!(set.contains(3) && set.contains(64));
+            assert!(!set.contains(2) && !set.contains(1 << 20));
+        }
+
+        let card: Card = "Card 1: 1 2 99999999999 | 99999999999 5 2".parse().unwrap();
+        assert_eq!(card.get_winning_count(), 2);
+
+        let result = "Card 1: 1 2 3 | 4 5 4".parse::<Card>().map(|_| ());
+        assert_eq!(result, Err(ParsingError::DuplicateNumber(4)));
+
+        let result = "Card 1: 7 2 7 | 4 5".parse::<Card>().map(|_| ());
+        assert_eq!(result, Err(ParsingError::DuplicateNumber(7)));
+    }
+
+    #[test]
+    fn scoring() {
+        let points = |scoring: &str| {
+            let scoring: Scoring = scoring.parse().unwrap();
+            (0..=6)
+                .map(|count| scoring.get_points(count).unwrap())
+                .collect::<Vec<u64>>()
+        };
+
+        assert_eq!(points("doubling"), vec![0, 1, 2, 4, 8, 16, 32]);
+        assert_eq!(points("linear"), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(points("fibonacci"), vec![0, 1, 2, 3, 5, 8, 13]);
+        assert_eq!(points("table:0,10,15"), vec![0, 10, 15, 15, 15, 15, 15]);
+
+        assert_eq!(Scoring::Doubling.get_points(65), None);
+        assert_eq!(Scoring::Doubling.get_points(64), Some(1 << 63));
+        assert_eq!(
+            Scoring::Fibonacci.get_points(92),
+            Some(12_200_160_415_121_876_738)
+        );
+        assert_eq!(Scoring::Fibonacci.get_points(93), None);
+        assert!("table:1,x".parse::<Scoring>().is_err());
+
+        let result = solve_part_1(SAMPLE, &Scoring::Linear).unwrap();
+        assert_eq!(result, 4 + 2 + 2 + 1);
+    }
+
+    // Points by doubling and copies of every card, by following each won copy
+    fn get_points_and_copies_naive(cards: &[(Vec<u64>, Vec<u64>)]) -> (u64, Vec<u128>) {
+        let matches: Vec<usize> = cards
+            .iter()
+            .map(|(winning, numbers)| winning.iter().filter(|e| numbers.contains(e)).count())
+            .collect();
+
+        let points = matches
+            .iter()
+            .map(|&m| if m == 0 { 0 } else { 1 << (m - 1) })
+            .sum();
+
+        let mut copies = vec![0; cards.len()];
+        let mut pending: Vec<usize> = (0..cards.len()).collect();
+
+        while let Some(i) = pending.pop() {
+            copies[i] += 1;
+            pending.extend((i + 1..=i + matches[i]).filter(|&e| e < cards.len()));
+        }
+
+        (points, copies)
+    }
+
+    #[test]
+    fn naive_matches_fast() {
+        // Some cards use numbers past the bitset limit to cover both number sets
+        check(
+            "day 4 points and copies",
+            |rng| {
+                (0..rng.range(0..8))
+                    .map(|_| {
+                        let offset = if rng.chance(1, 3) { BITSET_LIMIT } else { 0 };
+                        let mut pool = rng.sample(offset..offset + 16, 12);
+                        let numbers = pool.split_off(rng.index(6));
+                        let mut winning = pool;
+                        winning.extend(numbers.iter().take(rng.index(6)));
+
+                        (winning, numbers)
+                    })
+                    .collect::<Vec<_>>()
+            },
+            |cards| {
+                let parsed: Result<Vec<Card>, _> = (1..)
+                    .zip(cards)
+                    .map(|(id, (winning, numbers))| Card::new(id, winning.clone(), numbers))
+                    .collect();
+
+                // Shrinking can make numbers repeat, which cards reject
+                let Ok(mut parsed) = parsed else {
+                    return Ok(());
+                };
+
+                let fast = (
+                    get_total_points(&parsed, &Scoring::Doubling).unwrap(),
+                    get_copies(&mut parsed).unwrap(),
+                );
+
+                agree(get_points_and_copies_naive(cards), fast)
+            },
+        );
+    }
+
+    aoc_2023::fuzz_test!(4, 50, fuzz_input);
 }
@@ -1,7 +1,61 @@
-use std::str::FromStr;
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{fmt, ops::Range, path::PathBuf, str::FromStr};
 
-#[derive(Debug)]
-struct MapParsingError;
+const USAGE: &str = "05 [--input <path>]";
+
+#[derive(PartialEq, Eq, Debug)]
+enum MapParsingError {
+    MissingHeader,
+    InvalidNumber(String),
+    InvalidLine(String),
+    // A source or destination range that ends past `u64::MAX`
+    RangeOverflow(String),
+}
+
+impl fmt::Display for MapParsingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapParsingError::MissingHeader => write!(f, "missing \"<from>-to-<to> map:\" header"),
+            MapParsingError::InvalidNumber(n) => write!(f, "invalid number \"{n}\""),
+            MapParsingError::InvalidLine(line) => {
+                write!(f, "expected destination, source and length in \"{line}\"")
+            }
+            MapParsingError::RangeOverflow(line) => write!(f, "range too large in \"{line}\""),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+enum AlmanacError {
+    MissingSeeds,
+    InvalidSeed(String),
+    UnpairedSeed(u64),
+    SeedOverflow {
+        start: u64,
+        length: u64,
+    },
+    Map {
+        index: usize,
+        error: MapParsingError,
+    },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::MissingSeeds => write!(f, "missing \"seeds:\" line"),
+            AlmanacError::InvalidSeed(seed) => write!(f, "invalid seed \"{seed}\""),
+            AlmanacError::UnpairedSeed(seed) => write!(f, "seed {seed} has no range length"),
+            AlmanacError::SeedOverflow { start, length } => {
+                write!(f, "seed range {start} {length} is too large")
+            }
+            AlmanacError::Map { index, error } => write!(f, "map {}: {error}", index + 1),
+        }
+    }
+}
 
 struct Map {
     source: Vec<u64>,
@@ -19,6 +73,41 @@ impl Map {
 
         number
     }
+
+    // Splits the ranges at the map's boundaries; like `get`, the first line
+    // covering a number decides where it goes.
+    fn get_ranges(&self, mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+        let mut mapped = Vec::new();
+
+        for i in 0..self.source.len() {
+            let source = self.source[i]..self.source[i] + self.length[i];
+            let mut unmapped = Vec::new();
+
+            for range in ranges {
+                let start = range.start.max(source.start);
+                let end = range.end.min(source.end);
+
+                if start >= end {
+                    unmapped.push(range);
+                    continue;
+                }
+
+                mapped.push(
+                    self.dest[i] + (start - source.start)..self.dest[i] + (end - source.start),
+                );
+                unmapped.extend(
+                    [range.start..start, end..range.end]
+                        .into_iter()
+                        .filter(|e| !e.is_empty()),
+                );
+            }
+
+            ranges = unmapped;
+        }
+
+        mapped.extend(ranges);
+        mapped
+    }
 }
 
 impl FromStr for Map {
@@ -29,18 +118,34 @@ impl FromStr for Map {
         let mut dest = Vec::new();
         let mut length = Vec::new();
 
-        let mut it = s.split('\n').filter(|e| !e.is_empty());
-        let _header = it.next();
+        let mut it = s.lines().filter(|e| !e.is_empty());
+
+        if !it.next().is_some_and(|header| header.ends_with(" map:")) {
+            return Err(MapParsingError::MissingHeader);
+        }
 
         for line in it {
             let cur_map = line
                 .split_whitespace()
-                .map(|e| e.parse::<u64>().unwrap())
-                .collect::<Vec<u64>>();
+                .map(|e| {
+                    e.parse::<u64>()
+                        .map_err(|_| MapParsingError::InvalidNumber(e.to_owned()))
+                })
+                .collect::<Result<Vec<u64>, _>>()?;
+
+            let [cur_dest, cur_source, cur_length] = cur_map[..] else {
+                return Err(MapParsingError::InvalidLine(line.to_owned()));
+            };
 
-            dest.push(cur_map[0]);
-            source.push(cur_map[1]);
-            length.push(cur_map[2]);
+            if cur_dest.checked_add(cur_length).is_none()
+                || cur_source.checked_add(cur_length).is_none()
+            {
+                return Err(MapParsingError::RangeOverflow(line.to_owned()));
+            }
+
+            dest.push(cur_dest);
+            source.push(cur_source);
+            length.push(cur_length);
         }
 
         Ok(Map {
@@ -51,22 +156,58 @@ impl FromStr for Map {
     }
 }
 
-fn solve_part_1(input: &str) -> u64 {
-    let mut almanac = input.split("\n\n");
+fn parse_almanac(input: &str) -> Result<(Vec<u64>, Vec<Map>), AlmanacError> {
+    // Sections are separated by blank lines, whatever the line endings
+    let lines: Vec<&str> = input.lines().collect();
+    let mut almanac = lines
+        .split(|e| e.is_empty())
+        .filter(|e| !e.is_empty())
+        .map(|e| e.join("\n"));
 
     let seeds: Vec<u64> = almanac
         .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
+        .as_deref()
+        .and_then(|e| e.strip_prefix("seeds:"))
+        .ok_or(AlmanacError::MissingSeeds)?
         .split_whitespace()
-        .map(|e| e.parse().unwrap())
-        .collect();
+        .map(|e| {
+            e.parse()
+                .map_err(|_| AlmanacError::InvalidSeed(e.to_owned()))
+        })
+        .collect::<Result<_, _>>()?;
+
+    if seeds.is_empty() {
+        return Err(AlmanacError::MissingSeeds);
+    }
+
+    let maps: Vec<Map> = almanac
+        .enumerate()
+        .map(|(index, e)| {
+            e.parse::<Map>()
+                .map_err(|error| AlmanacError::Map { index, error })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((seeds, maps))
+}
 
-    let maps: Vec<Map> = almanac.map(|e| e.parse::<Map>().unwrap()).collect();
+fn get_lowest_location(seeds: &[Range<u64>], maps: &[Map]) -> u64 {
+    let ranges = maps
+        .iter()
+        .fold(seeds.to_vec(), |ranges, map| map.get_ranges(ranges));
+
+    ranges
+        .iter()
+        .filter(|e| !e.is_empty())
+        .map(|e| e.start)
+        .min()
+        .unwrap_or(u64::MAX)
+}
 
-    seeds
+fn solve_part_1(input: &str) -> Result<u64, AlmanacError> {
+    let (seeds, maps) = parse_almanac(input)?;
+
+    let lowest = seeds
         .iter()
         .map(|seed| {
             let mut cur_value = seed.to_owned();
@@ -78,75 +219,177 @@ fn solve_part_1(input: &str) -> u64 {
             cur_value
         })
         .min()
-        .unwrap()
+        .unwrap();
+
+    Ok(lowest)
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let mut almanac = input.split("\n\n");
+fn solve_part_2(input: &str) -> Result<u64, AlmanacError> {
+    let (seeds_data, maps) = parse_almanac(input)?;
 
-    let seeds_data: Vec<u64> = almanac
-        .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .map(|e| e.parse().unwrap())
-        .collect();
+    let seeds: Vec<Range<u64>> = seeds_data
+        .chunks(2)
+        .map(|e| match *e {
+            [start, length] => start
+                .checked_add(length)
+                .map(|end| start..end)
+                .ok_or(AlmanacError::SeedOverflow { start, length }),
+            _ => Err(AlmanacError::UnpairedSeed(e[0])),
+        })
+        .collect::<Result<_, _>>()?;
 
-    let mut seeds = Vec::new();
+    Ok(get_lowest_location(&seeds, &maps))
+}
 
-    let mut seed_start = 0;
-    for (i, seed) in seeds_data.iter().enumerate() {
-        if i % 2 == 0 {
-            seed_start = *seed;
-        } else {
-            seeds.push(seed_start..seed_start + seed);
-        }
+// Entry point for the `day_05` fuzz target.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = solve_part_1(input);
+        let _ = solve_part_2(input);
     }
+}
 
-    let maps: Vec<Map> = almanac.map(|e| e.parse::<Map>().unwrap()).collect();
-    let mut min: u64 = u64::MAX;
-
-    for seed_range in seeds {
-        for seed in seed_range {
-            let mut cur_value = seed;
-
-            for e in &maps {
-                cur_value = e.get(cur_value);
-            }
+fn main() {
+    let mut path = input_path(5);
 
-            if cur_value < min {
-                min = cur_value;
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" => path = PathBuf::from(args.value(&arg, "an almanac file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
             }
         }
-    }
 
-    min
-}
+        Ok(())
+    });
 
-fn main() {
-    let input = include_str!("../../data/input/05.txt");
+    let input = read_input(&path);
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::print_result(1, solve_part_1(&input));
+    cli::print_result(2, solve_part_2(&input));
 }
 
 #[cfg(test)]
 mod day05_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_input, get_lowest_location, solve_part_1, solve_part_2, AlmanacError, Map,
+        MapParsingError,
+    };
+    use aoc_2023::{
+        input::Case,
+        prop::{agree, check},
+    };
+    use std::ops::Range;
+
+    fn get_lowest_location_naive(seeds: &[Range<u64>], maps: &[Map]) -> u64 {
+        seeds
+            .iter()
+            .flat_map(|range| range.clone())
+            .map(|seed| maps.iter().fold(seed, |value, map| map.get(value)))
+            .min()
+            .unwrap_or(u64::MAX)
+    }
 
-    const SAMPLE: &str = include_str!("../../data/sample/05.txt");
+    fn check_case(case: &Case) {
+        case.check(1, || solve_part_1(&case.input).unwrap());
+        case.check(2, || solve_part_2(&case.input).unwrap());
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/samples_05.rs"));
+
+    #[test]
+    fn golden() {
+        if let Some(case) = Case::golden(5) {
+            check_case(&case);
+        }
+    }
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 35);
+    fn naive_matches_ranges() {
+        // Small numbers so that seed ranges and map lines overlap a lot
+        check(
+            "day 5 lowest location",
+            |rng| {
+                let mut range = |end| rng.range(0..end);
+                let seeds: Vec<(u64, u64)> =
+                    (0..range(4) + 1).map(|_| (range(100), range(30))).collect();
+                let maps: Vec<Vec<(u64, u64, u64)>> = (0..range(4) + 1)
+                    .map(|_| {
+                        (0..range(5))
+                            .map(|_| (range(100), range(100), range(40)))
+                            .collect()
+                    })
+                    .collect();
+
+                (seeds, maps)
+            },
+            |(seeds, maps)| {
+                let seeds: Vec<Range<u64>> = seeds
+                    .iter()
+                    .map(|&(start, length)| start..start + length)
+                    .collect();
+                let maps: Vec<Map> = maps
+                    .iter()
+                    .map(|lines| Map {
+                        dest: lines.iter().map(|e| e.0).collect(),
+                        source: lines.iter().map(|e| e.1).collect(),
+                        length: lines.iter().map(|e| e.2).collect(),
+                    })
+                    .collect();
+
+                agree(
+                    get_lowest_location_naive(&seeds, &maps),
+                    get_lowest_location(&seeds, &maps),
+                )
+            },
+        );
     }
 
+    aoc_2023::fuzz_test!(5, 1000, fuzz_input);
+
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 46);
+    fn invalid_almanacs() {
+        let maps = "\n\nseed-to-soil map:\n50 98 2";
+        assert_eq!(solve_part_1(&format!("seeds: 79{maps}")), Ok(79));
+        assert_eq!(
+            solve_part_2(&format!("seeds: 79{maps}")),
+            Err(AlmanacError::UnpairedSeed(79))
+        );
+        assert_eq!(solve_part_1(maps), Err(AlmanacError::MissingSeeds));
+        assert_eq!(
+            solve_part_1("seeds: 1 x"),
+            Err(AlmanacError::InvalidSeed(String::from("x")))
+        );
+
+        let result = solve_part_1("seeds: 1\n\nseed-to-soil map:\n50 98");
+        assert_eq!(
+            result,
+            Err(AlmanacError::Map {
+                index: 0,
+                error: MapParsingError::InvalidLine(String::from("50 98"))
+            })
+        );
+
+        let result = solve_part_1(&format!("seeds: 1\n\nseed-to-soil map:\n0 {} 2", u64::MAX));
+        assert!(matches!(
+            result,
+            Err(AlmanacError::Map {
+                index: 0,
+                error: MapParsingError::RangeOverflow(_)
+            })
+        ));
+
+        let crlf = "seeds: 79\r\n\r\nseed-to-soil map:\r\n50 98 2\r\n\r\n\r\nsoil-to-fertilizer map:\r\n0 79 1\r\n";
+        assert_eq!(solve_part_1(crlf), Ok(0));
+
+        let result = solve_part_1("seeds: 1\n\nseed-to-soil map:\n\n1 2 3");
+        assert_eq!(
+            result,
+            Err(AlmanacError::Map {
+                index: 1,
+                error: MapParsingError::MissingHeader
+            })
+        );
     }
 }
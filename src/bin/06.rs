@@ -1,3 +1,32 @@
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{fmt, path::PathBuf};
+
+const USAGE: &str = "06 [--input <path>]";
+
+#[derive(PartialEq, Eq, Debug)]
+enum SheetError {
+    MissingLine(&'static str),
+    InvalidNumber(String),
+    ColumnMismatch { times: usize, distances: usize },
+    Overflow,
+}
+
+impl fmt::Display for SheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SheetError::MissingLine(label) => write!(f, "missing \"{label}:\" line"),
+            SheetError::InvalidNumber(n) => write!(f, "invalid number \"{n}\""),
+            SheetError::ColumnMismatch { times, distances } => {
+                write!(f, "{times} times but {distances} distances")
+            }
+            SheetError::Overflow => write!(f, "result does not fit in 64 bits"),
+        }
+    }
+}
+
 struct Race {
     time: u64,
     distance_record: u64,
@@ -11,41 +40,59 @@ impl Race {
         }
     }
 
+    // The distance for holding the button `i` ms is i * (time - i), a parabola
+    // symmetric around time / 2, so only the first winning hold is searched.
     fn get_possible_solutions(&self) -> u64 {
-        let mut start = 0;
-        let mut end = 0;
+        let (time, record) = (u128::from(self.time), u128::from(self.distance_record));
+        let beats = |i: u128| i * (time - i) > record;
 
-        for i in 1..self.time {
-            if (self.time - i) * i > self.distance_record {
-                end = i;
-            }
+        if !beats(time / 2) {
+            return 0;
         }
 
-        for i in (1..self.time).rev() {
-            if (self.time - i) * i > self.distance_record {
-                start = i;
-            }
+        // The quadratic formula up to rounding, which the loops correct
+        let mut first = (time - (time * time - 4 * record).isqrt()) / 2;
+
+        while first > 0 && beats(first - 1) {
+            first -= 1;
+        }
+
+        while !beats(first) {
+            first += 1;
         }
 
-        end - start + 1
+        u64::try_from(time - 2 * first + 1).unwrap()
     }
 }
 
-fn parse_line_to_vec(line: &str) -> Vec<u64> {
-    line.split(':')
-        .nth(1)
-        .unwrap()
+// The numbers after `label:`, which must start the line.
+fn get_line_numbers<'a>(line: Option<&'a str>, label: &'static str) -> Result<&'a str, SheetError> {
+    line.and_then(|e| e.strip_prefix(label)?.strip_prefix(':'))
+        .ok_or(SheetError::MissingLine(label))
+}
+
+fn parse_line_to_vec(line: Option<&str>, label: &'static str) -> Result<Vec<u64>, SheetError> {
+    get_line_numbers(line, label)?
         .split_whitespace()
-        .map(|e| e.parse().unwrap())
-        .collect::<Vec<u64>>()
+        .map(|e| {
+            e.parse()
+                .map_err(|_| SheetError::InvalidNumber(e.to_owned()))
+        })
+        .collect()
 }
 
-fn solve_part_1(input: &str) -> u64 {
+fn solve_part_1(input: &str) -> Result<u64, SheetError> {
     let mut lines = input.lines();
-    let (time, distance) = (lines.next().unwrap(), lines.next().unwrap());
 
-    let time = parse_line_to_vec(time);
-    let distance = parse_line_to_vec(distance);
+    let time = parse_line_to_vec(lines.next(), "Time")?;
+    let distance = parse_line_to_vec(lines.next(), "Distance")?;
+
+    if time.len() != distance.len() {
+        return Err(SheetError::ColumnMismatch {
+            times: time.len(),
+            distances: distance.len(),
+        });
+    }
 
     let mut races = Vec::new();
 
@@ -56,53 +103,135 @@ fn solve_part_1(input: &str) -> u64 {
     races
         .iter()
         .map(Race::get_possible_solutions)
-        .product::<u64>()
+        .try_fold(1_u64, |product, e| product.checked_mul(e))
+        .ok_or(SheetError::Overflow)
 }
 
-fn parse_line_to_value(line: &str) -> u64 {
-    line.split(':')
-        .nth(1)
-        .unwrap()
+fn parse_line_to_value(line: Option<&str>, label: &'static str) -> Result<u64, SheetError> {
+    let digits = get_line_numbers(line, label)?
         .chars()
         .filter(|e| !e.is_whitespace())
-        .collect::<String>()
+        .collect::<String>();
+
+    digits
         .parse()
-        .unwrap()
+        .map_err(|_| SheetError::InvalidNumber(digits))
 }
 
-fn solve_part_2(input: &str) -> u64 {
+fn solve_part_2(input: &str) -> Result<u64, SheetError> {
     let mut lines = input.lines();
-    let (time, distance) = (lines.next().unwrap(), lines.next().unwrap());
 
-    let time = parse_line_to_value(time);
-    let distance = parse_line_to_value(distance);
+    let time = parse_line_to_value(lines.next(), "Time")?;
+    let distance = parse_line_to_value(lines.next(), "Distance")?;
     let race = Race::new(time, distance);
 
-    race.get_possible_solutions()
+    Ok(race.get_possible_solutions())
+}
+
+// Entry point for the `day_06` fuzz target.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = solve_part_1(input);
+        let _ = solve_part_2(input);
+    }
 }
 
 fn main() {
-    let input = include_str!("../../data/input/06.txt");
+    let mut path = input_path(6);
+
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input" => path = PathBuf::from(args.value(&arg, "a race sheet file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(())
+    });
+
+    let input = read_input(&path);
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::print_result(1, solve_part_1(&input));
+    cli::print_result(2, solve_part_2(&input));
 }
 
 #[cfg(test)]
 mod day06_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{fuzz_input, solve_part_1, solve_part_2, Race, SheetError};
+    use aoc_2023::{
+        input::Case,
+        prop::{agree, check},
+    };
+
+    fn get_possible_solutions_naive(race: &Race) -> u64 {
+        (1..race.time)
+            .filter(|i| (race.time - i) * i > race.distance_record)
+            .count() as u64
+    }
+
+    fn check_case(case: &Case) {
+        case.check(1, || solve_part_1(&case.input).unwrap());
+        case.check(2, || solve_part_2(&case.input).unwrap());
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/samples_06.rs"));
 
-    const SAMPLE: &str = include_str!("../../data/sample/06.txt");
+    #[test]
+    fn golden() {
+        if let Some(case) = Case::golden(6) {
+            check_case(&case);
+        }
+    }
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 288);
+    fn naive_matches_closed_form() {
+        check(
+            "day 6 solutions",
+            |rng| {
+                let time = rng.range(0..2000);
+                (time, rng.range(0..time * time / 4 + 10))
+            },
+            |&(time, record)| {
+                let race = Race::new(time, record);
+                agree(
+                    get_possible_solutions_naive(&race),
+                    race.get_possible_solutions(),
+                )
+            },
+        );
+
+        assert_eq!(
+            Race::new(u64::MAX, 0).get_possible_solutions(),
+            u64::MAX - 1
+        );
     }
 
+    aoc_2023::fuzz_test!(6, 6, fuzz_input);
+
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 71503);
+    fn invalid_sheets() {
+        assert_eq!(
+            solve_part_1("Time: 7 15\nDistance: 9"),
+            Err(SheetError::ColumnMismatch {
+                times: 2,
+                distances: 1
+            })
+        );
+        assert_eq!(
+            solve_part_1("Time: 7\n"),
+            Err(SheetError::MissingLine("Distance"))
+        );
+        assert_eq!(
+            solve_part_2("Distance: 9\nTime: 7"),
+            Err(SheetError::MissingLine("Time"))
+        );
+        assert_eq!(
+            solve_part_2("Time: 7 -1\nDistance: 9 0"),
+            Err(SheetError::InvalidNumber(String::from("7-1")))
+        );
+        assert_eq!(solve_part_1("Time: 7\nDistance: 10"), Ok(2));
+        assert_eq!(solve_part_1("Time: 7\nDistance: 12"), Ok(0));
     }
 }
@@ -1,8 +1,18 @@
-use std::hash::Hash;
-use std::{collections::HashMap, str::FromStr};
+use aoc_2023::{
+    cli::{self, UsageError},
+    input::{input_path, read_input},
+};
+use std::{fmt, path::PathBuf, str::FromStr};
 
-trait Cardable {
-    fn from_char(c: char) -> Self;
+const USAGE: &str = "07 [--input <path>] [--skip-invalid]";
+
+trait Cardable: Copy {
+    fn from_char(c: char) -> Option<Self>;
+    fn rank(self) -> u32;
+
+    fn is_joker(self) -> bool {
+        false
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash, Debug)]
@@ -23,8 +33,8 @@ enum Card {
 }
 
 impl Cardable for Card {
-    fn from_char(c: char) -> Self {
-        match c {
+    fn from_char(c: char) -> Option<Self> {
+        let card = match c {
             '2' => Card::Two,
             '3' => Card::Three,
             '4' => Card::Four,
@@ -38,8 +48,14 @@ impl Cardable for Card {
             'Q' => Card::Queen,
             'K' => Card::King,
             'A' => Card::Ace,
-            _ => panic!("Not a valid card"),
-        }
+            _ => return None,
+        };
+
+        Some(card)
+    }
+
+    fn rank(self) -> u32 {
+        self as u32
     }
 }
 
@@ -61,8 +77,8 @@ enum CardWithJoker {
 }
 
 impl Cardable for CardWithJoker {
-    fn from_char(c: char) -> Self {
-        match c {
+    fn from_char(c: char) -> Option<Self> {
+        let card = match c {
             'J' => CardWithJoker::Joker,
             '2' => CardWithJoker::Two,
             '3' => CardWithJoker::Three,
@@ -76,35 +92,48 @@ impl Cardable for CardWithJoker {
             'Q' => CardWithJoker::Queen,
             'K' => CardWithJoker::King,
             'A' => CardWithJoker::Ace,
-            _ => panic!("Not a valid card"),
-        }
+            _ => return None,
+        };
+
+        Some(card)
+    }
+
+    fn rank(self) -> u32 {
+        self as u32
+    }
+
+    fn is_joker(self) -> bool {
+        self == CardWithJoker::Joker
     }
 }
 
 type Hand<T> = [T; 5];
 
-fn get_card_count<T>(arr: [T; 5]) -> HashMap<T, u64>
+const CARD_KINDS: usize = 13;
+
+fn get_card_count<T>(hand: Hand<T>) -> ([u8; CARD_KINDS], u8)
 where
     T: Cardable,
-    T: Eq,
-    T: Hash,
-    T: Copy,
 {
-    let mut counts: HashMap<T, u64> = HashMap::new();
+    let mut counts = [0; CARD_KINDS];
+    let mut jokers = 0;
 
-    for card in arr {
-        if counts.contains_key(&card) {
-            counts.insert(card, counts.get(&card).unwrap() + 1);
+    for card in hand {
+        if card.is_joker() {
+            jokers += 1;
         } else {
-            counts.insert(card, 1);
+            counts[card.rank() as usize] += 1;
         }
     }
 
-    counts
+    (counts, jokers)
 }
 
-#[derive(Debug)]
-struct HandTypeParseErr;
+#[derive(PartialEq, Eq, Debug)]
+enum HandTypeParseErr {
+    InvalidLength(usize),
+    InvalidCard { index: usize, card: char },
+}
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
 enum HandType<T>
@@ -120,164 +149,391 @@ where
     FiveOfKind(Hand<T>),
 }
 
-fn from_cards<T>(max_count: u64, hand: Hand<T>, actual_hand: Hand<T>) -> HandType<T>
+impl<T> HandType<T>
 where
-    T: std::cmp::PartialEq,
     T: Cardable,
-    T: Copy,
 {
-    match max_count {
-        5 => HandType::FiveOfKind(actual_hand),
-        4 => HandType::FourOfKind(actual_hand),
-        3 => {
-            for c in &hand {
-                if hand.iter().filter(|e| *e == c).count() == 2 {
-                    return HandType::FullHouse(actual_hand);
-                }
-            }
+    // Category in bits 20..23, followed by one nibble per card rank, so that
+    // comparing keys orders hands exactly like the derived `Ord`.
+    fn sort_key(&self) -> u32 {
+        let (category, hand) = match self {
+            HandType::HighCard(hand) => (0, hand),
+            HandType::OnePair(hand) => (1, hand),
+            HandType::TwoPair(hand) => (2, hand),
+            HandType::ThreeOfKind(hand) => (3, hand),
+            HandType::FullHouse(hand) => (4, hand),
+            HandType::FourOfKind(hand) => (5, hand),
+            HandType::FiveOfKind(hand) => (6, hand),
+        };
+
+        hand.iter()
+            .fold(category, |key, card| key << 4 | card.rank())
+    }
+}
 
-            HandType::ThreeOfKind(actual_hand)
-        }
-        2 => {
-            let mut prev_two_count: Option<T> = None;
-
-            for cur_card in &hand {
-                if hand.iter().filter(|e| *e == cur_card).count() == 2 {
-                    if prev_two_count.is_none() {
-                        prev_two_count = Some(*cur_card);
-                    } else if prev_two_count.unwrap() != *cur_card {
-                        return HandType::TwoPair(actual_hand);
-                    }
-                }
-            }
+fn from_cards<T>(hand: Hand<T>) -> HandType<T>
+where
+    T: Cardable,
+{
+    let (counts, jokers) = get_card_count(hand);
 
-            HandType::OnePair(actual_hand)
+    let mut max_count = 0;
+    let mut second_count = 0;
+
+    for count in counts {
+        if count > max_count {
+            second_count = max_count;
+            max_count = count;
+        } else if count > second_count {
+            second_count = count;
         }
-        _ => HandType::HighCard(actual_hand),
+    }
+
+    match (max_count + jokers, second_count) {
+        (5, _) => HandType::FiveOfKind(hand),
+        (4, _) => HandType::FourOfKind(hand),
+        (3, 2) => HandType::FullHouse(hand),
+        (3, _) => HandType::ThreeOfKind(hand),
+        (2, 2) => HandType::TwoPair(hand),
+        (2, _) => HandType::OnePair(hand),
+        _ => HandType::HighCard(hand),
     }
 }
 
-impl FromStr for HandType<Card> {
+impl<T> FromStr for HandType<T>
+where
+    T: Cardable,
+{
     type Err = HandTypeParseErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut hand: [Card; 5] = [Card::Two; 5];
+        let mut hand: [Option<T>; 5] = [None; 5];
+        let mut length = 0;
+
+        for (index, (_, card)) in s.char_indices().enumerate() {
+            let card = T::from_char(card).ok_or(HandTypeParseErr::InvalidCard { index, card })?;
+
+            if let Some(slot) = hand.get_mut(length) {
+                *slot = Some(card);
+            }
+
+            length += 1;
+        }
 
-        for (i, c) in s.chars().enumerate() {
-            hand[i] = Card::from_char(c);
+        if length != 5 {
+            return Err(HandTypeParseErr::InvalidLength(length));
         }
 
         Ok(from_cards(
-            *get_card_count(hand).values().max().unwrap(),
-            hand,
-            hand,
+            hand.map(|card| card.expect("Hand length should be checked")),
         ))
     }
 }
 
-impl FromStr for HandType<CardWithJoker> {
-    type Err = HandTypeParseErr;
+#[derive(PartialEq, Eq, Debug)]
+enum LineErrorKind {
+    MissingHand,
+    InvalidHandLength(usize),
+    InvalidCard(char),
+    MissingBid,
+    InvalidBid(String),
+    UnexpectedField(String),
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut hand: [CardWithJoker; 5] = [CardWithJoker::Two; 5];
+#[derive(PartialEq, Eq, Debug)]
+struct LineParseErr {
+    line: usize,
+    column: usize,
+    kind: LineErrorKind,
+}
 
-        for (i, c) in s.chars().enumerate() {
-            hand[i] = CardWithJoker::from_char(c);
-        }
+impl fmt::Display for LineParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
 
-        let hand_with_joker = hand;
+        match &self.kind {
+            LineErrorKind::MissingHand => write!(f, "missing hand"),
+            LineErrorKind::InvalidHandLength(length) => {
+                write!(f, "hand has {length} cards, expected 5")
+            }
+            LineErrorKind::InvalidCard(card) => write!(f, "invalid card {card:?}"),
+            LineErrorKind::MissingBid => write!(f, "missing bid"),
+            LineErrorKind::InvalidBid(bid) => write!(f, "invalid bid {bid:?}"),
+            LineErrorKind::UnexpectedField(field) => write!(f, "unexpected field {field:?}"),
+        }
+    }
+}
 
-        let counts = get_card_count(hand);
-        let mut max_count = *counts.values().max().unwrap();
+#[derive(PartialEq, Eq, Debug)]
+enum WinningsErr {
+    InvalidLines(Vec<LineParseErr>),
+    Overflow,
+}
 
-        if counts.get(&CardWithJoker::Joker).is_some() {
-            let mut counts_vec: Vec<(&CardWithJoker, &u64)> = counts.iter().collect();
-            counts_vec.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)));
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum InvalidLinePolicy {
+    Reject,
+    Skip,
+}
 
-            if *counts_vec.last().unwrap().0 == CardWithJoker::Joker {
-                counts_vec.pop();
-            }
+// Columns count chars, not bytes, so they match what an editor shows.
+fn parse_line<T>(line: &str, line_number: usize) -> Result<(HandType<T>, u64), LineParseErr>
+where
+    T: Cardable,
+{
+    let error = |column: usize, kind: LineErrorKind| LineParseErr {
+        line: line_number,
+        column: column + 1,
+        kind,
+    };
+
+    let mut fields = line
+        .split(|c: char| c.is_ascii_whitespace())
+        .scan(0, |offset, field| {
+            let column = *offset;
+            *offset += field.chars().count() + 1;
+            Some((column, field))
+        })
+        .filter(|(_, field)| !field.is_empty());
 
-            if max_count != 5 {
-                for (i, card) in hand_with_joker.iter().enumerate() {
-                    if *card == CardWithJoker::Joker {
-                        hand[i] = *counts_vec.last().unwrap().0;
-                    }
-                }
-            }
+    let (hand_column, hand) = fields
+        .next()
+        .ok_or_else(|| error(0, LineErrorKind::MissingHand))?;
 
-            max_count = *get_card_count(hand).values().max().unwrap();
+    let hand = hand.parse::<HandType<T>>().map_err(|err| match err {
+        HandTypeParseErr::InvalidLength(length) => {
+            error(hand_column, LineErrorKind::InvalidHandLength(length))
         }
-
-        Ok(from_cards(max_count, hand, hand_with_joker))
+        HandTypeParseErr::InvalidCard { index, card } => {
+            error(hand_column + index, LineErrorKind::InvalidCard(card))
+        }
+    })?;
+
+    let (bid_column, bid) = fields
+        .next()
+        .ok_or_else(|| error(line.chars().count(), LineErrorKind::MissingBid))?;
+
+    let bid = bid
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| bid.parse::<u64>().ok())
+        .flatten()
+        .ok_or_else(|| error(bid_column, LineErrorKind::InvalidBid(bid.to_owned())))?;
+
+    if let Some((column, field)) = fields.next() {
+        return Err(error(
+            column,
+            LineErrorKind::UnexpectedField(field.to_owned()),
+        ));
     }
+
+    Ok((hand, bid))
 }
 
-fn solve_part_1(input: &str) -> u64 {
-    let mut hands: Vec<(HandType<Card>, u64)> = input
-        .lines()
-        .map(|e| {
-            let mut it = e.split_whitespace();
+fn get_total_winnings<T>(input: &str, policy: InvalidLinePolicy) -> Result<u64, WinningsErr>
+where
+    T: Cardable,
+{
+    let mut hands: Vec<(u32, u64)> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in input.lines().enumerate() {
+        match parse_line::<T>(line, i + 1) {
+            Ok((hand, bid)) => hands.push((hand.sort_key(), bid)),
+            Err(err) if policy == InvalidLinePolicy::Skip => {
+                eprintln!("Warning: skipping {err}");
+            }
+            Err(err) => errors.push(err),
+        }
+    }
 
-            let hand: HandType<Card> = it.next().unwrap().parse().unwrap();
-            let bid: u64 = it.next().unwrap().parse().unwrap();
+    if !errors.is_empty() {
+        return Err(WinningsErr::InvalidLines(errors));
+    }
 
-            (hand, bid)
-        })
-        .collect();
+    // Stable, so identical hands keep their input order
+    hands.sort_by_key(|e| e.0);
 
-    hands.sort_by(|a, b| a.0.cmp(&b.0));
     hands
         .iter()
-        .enumerate()
-        .map(|(i, e)| u64::try_from(i + 1).unwrap() * e.1)
-        .sum()
+        .zip(1_u64..)
+        .try_fold(0_u64, |sum, (e, rank)| {
+            sum.checked_add(rank.checked_mul(e.1)?)
+        })
+        .ok_or(WinningsErr::Overflow)
 }
 
-fn solve_part_2(input: &str) -> u64 {
-    let mut hands: Vec<(HandType<CardWithJoker>, u64)> = input
-        .lines()
-        .map(|e| {
-            let mut it = e.split_whitespace();
+fn solve_part_1(input: &str, policy: InvalidLinePolicy) -> Result<u64, WinningsErr> {
+    get_total_winnings::<Card>(input, policy)
+}
 
-            let hand: HandType<CardWithJoker> = it.next().unwrap().parse().unwrap();
-            let bid: u64 = it.next().unwrap().parse().unwrap();
+fn solve_part_2(input: &str, policy: InvalidLinePolicy) -> Result<u64, WinningsErr> {
+    get_total_winnings::<CardWithJoker>(input, policy)
+}
 
-            (hand, bid)
-        })
-        .collect();
+fn print_result(part: u8, result: Result<u64, WinningsErr>) {
+    match result {
+        Ok(result) => println!("Part {part}: {result}"),
+        Err(WinningsErr::InvalidLines(errors)) => {
+            for err in errors {
+                eprintln!("Part {part}: {err}");
+            }
+        }
+        Err(WinningsErr::Overflow) => eprintln!("Part {part}: result does not fit in 64 bits"),
+    }
+}
 
-    hands.sort_by(|a, b| a.0.cmp(&b.0));
+// Entry point for the `day_07` fuzz target.
+#[cfg(any(test, fuzzing))]
+pub fn fuzz_input(data: &[u8]) {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
 
-    hands
-        .iter()
-        .enumerate()
-        .map(|(i, e)| u64::try_from(i + 1).unwrap() * e.1)
-        .sum()
+    // Skipping only differs in printing a warning per invalid line
+    let _ = solve_part_1(input, InvalidLinePolicy::Reject);
+    let _ = solve_part_2(input, InvalidLinePolicy::Reject);
 }
 
 fn main() {
-    let input = include_str!("../../data/input/07.txt");
+    let mut path = input_path(7);
+
+    let mut policy = InvalidLinePolicy::Reject;
 
-    println!("Part 1: {}", solve_part_1(input));
-    println!("Part 2: {}", solve_part_2(input));
+    cli::parse_args(USAGE, |args| {
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--skip-invalid" => policy = InvalidLinePolicy::Skip,
+                "--input" => path = PathBuf::from(args.value(&arg, "a hand list file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(())
+    });
+
+    let input = read_input(&path);
+
+    print_result(1, solve_part_1(&input, policy));
+    print_result(2, solve_part_2(&input, policy));
 }
 
 #[cfg(test)]
 mod day07_test {
-    use crate::{solve_part_1, solve_part_2};
+    use crate::{
+        fuzz_input, solve_part_1, solve_part_2, Card, CardWithJoker, HandType, InvalidLinePolicy,
+        LineErrorKind, LineParseErr, WinningsErr,
+    };
+    use aoc_2023::input::Case;
+
+    fn check_case(case: &Case) {
+        case.check(1, || {
+            solve_part_1(&case.input, InvalidLinePolicy::Reject).unwrap()
+        });
+        case.check(2, || {
+            solve_part_2(&case.input, InvalidLinePolicy::Reject).unwrap()
+        });
+    }
 
-    const SAMPLE: &str = include_str!("../../data/sample/07.txt");
+    include!(concat!(env!("OUT_DIR"), "/samples_07.rs"));
 
     #[test]
-    fn part_1() {
-        let result = solve_part_1(SAMPLE);
-        assert_eq!(result, 6440);
+    fn golden() {
+        if let Some(case) = Case::golden(7) {
+            check_case(&case);
+        }
     }
 
     #[test]
-    fn part_2() {
-        let result = solve_part_2(SAMPLE);
-        assert_eq!(result, 5905);
+    fn sort_key_matches_ord() {
+        let hands: Vec<HandType<Card>> = ["32T3K", "T55J5", "KK677", "KTJJT", "QQQJA", "AAAAA"]
+            .iter()
+            .map(|e| e.parse().unwrap())
+            .collect();
+
+        for a in &hands {
+            for b in &hands {
+                assert_eq!(a.cmp(b), a.sort_key().cmp(&b.sort_key()));
+            }
+        }
+
+        let joker: HandType<CardWithJoker> = "JJJJJ".parse().unwrap();
+        assert_eq!(joker, HandType::FiveOfKind([CardWithJoker::Joker; 5]));
     }
+
+    #[test]
+    fn repeated_hands() {
+        // Every third line holds the stronger hand, and each line bids its own
+        // line number, so only ranking identical hands in input order gives
+        // the expected winnings.
+        let stronger = |n: u64| n.is_multiple_of(3);
+        let input: Vec<String> = (1..=300)
+            .map(|n| format!("{} {n}", if stronger(n) { "KK677" } else { "32T3K" }))
+            .collect();
+        let (strong, weak): (Vec<u64>, Vec<u64>) = (1..=300).partition(|&n| stronger(n));
+        let expected = weak
+            .iter()
+            .chain(&strong)
+            .zip(1..)
+            .map(|(bid, rank)| bid * rank)
+            .sum();
+
+        assert_eq!(
+            solve_part_1(&input.join("\n"), InvalidLinePolicy::Reject),
+            Ok(expected)
+        );
+        assert_eq!(
+            solve_part_2(&input.join("\n"), InvalidLinePolicy::Reject),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let input = "32T3K 765\nT55J5A 684\nKK67 28\nKTXJT 220\nQQQJA 4x3\nAAAAA\n";
+
+        let Err(WinningsErr::InvalidLines(errors)) = solve_part_1(input, InvalidLinePolicy::Reject)
+        else {
+            panic!("Invalid lines should be rejected");
+        };
+        let kinds: Vec<(usize, usize, LineErrorKind)> = errors
+            .into_iter()
+            .map(|LineParseErr { line, column, kind }| (line, column, kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (2, 1, LineErrorKind::InvalidHandLength(6)),
+                (3, 1, LineErrorKind::InvalidHandLength(4)),
+                (4, 3, LineErrorKind::InvalidCard('X')),
+                (5, 7, LineErrorKind::InvalidBid(String::from("4x3"))),
+                (6, 6, LineErrorKind::MissingBid),
+            ]
+        );
+
+        let result = solve_part_1(input, InvalidLinePolicy::Skip).unwrap();
+        assert_eq!(result, 765);
+
+        let input = "KK\u{1f0a1}77 5\nÄÖ 1\n32T3K\u{a0} 1";
+        let Err(WinningsErr::InvalidLines(errors)) = solve_part_1(input, InvalidLinePolicy::Reject)
+        else {
+            panic!("Invalid lines should be rejected");
+        };
+        let columns: Vec<(usize, usize)> = errors.iter().map(|e| (e.line, e.column)).collect();
+
+        assert_eq!(columns, vec![(1, 3), (2, 1), (3, 6)]);
+        assert_eq!(
+            errors[0].to_string(),
+            "line 1, column 3: invalid card '\u{1f0a1}'"
+        );
+
+        let result = solve_part_1(
+            &format!("32T3K 1\nKK677 {}", u64::MAX),
+            InvalidLinePolicy::Reject,
+        );
+        assert_eq!(result, Err(WinningsErr::Overflow));
+    }
+
+    aoc_2023::fuzz_test!(7, 100, fuzz_input);
 }
@@ -0,0 +1,44 @@
+use std::fs;
+
+use aoc_2023::{
+    cli::{self, UsageError},
+    generate::{default_size, generate},
+};
+
+const USAGE: &str = "generate <day> [--seed <seed>] [--size <size>] [--output <path>]";
+
+fn main() {
+    let mut day = 0;
+    let mut seed = 0;
+    let mut size = 0;
+    let mut output = None;
+
+    cli::parse_args(USAGE, |args| {
+        let value = args.value("<day>", "a day such as 7 or 07")?;
+        day = value
+            .parse()
+            .map_err(|err| UsageError::invalid("<day>", &value, err))?;
+        size = default_size(day)
+            .ok_or_else(|| UsageError::invalid("<day>", &value, "no generator for this day"))?;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => seed = args.parse(&arg, "a seed")?,
+                "--size" => size = args.parse(&arg, "a size")?,
+                "--output" => output = Some(args.value(&arg, "an output file path")?),
+                _ => return Err(UsageError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(())
+    });
+
+    let input = generate(day, seed, size).unwrap();
+
+    match output {
+        Some(path) => {
+            fs::write(&path, input).unwrap_or_else(|err| panic!("Could not write {path}: {err}"))
+        }
+        None => print!("{input}"),
+    }
+}
@@ -0,0 +1,131 @@
+//! Command line handling shared by the days' binaries.
+
+use std::{env, fmt, process, str::FromStr};
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum UsageError {
+    MissingValue {
+        flag: String,
+        expected: &'static str,
+    },
+    InvalidValue {
+        flag: String,
+        value: String,
+        reason: String,
+    },
+    UnknownArgument(String),
+}
+
+impl UsageError {
+    pub fn invalid(flag: &str, value: &str, reason: impl fmt::Display) -> Self {
+        UsageError::InvalidValue {
+            flag: flag.to_owned(),
+            value: value.to_owned(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UsageError::MissingValue { flag, expected } => {
+                write!(f, "{flag}: missing value, expected {expected}")
+            }
+            UsageError::InvalidValue {
+                flag,
+                value,
+                reason,
+            } => write!(f, "{flag}: invalid value {value:?}: {reason}"),
+            UsageError::UnknownArgument(arg) => write!(f, "unknown argument {arg:?}"),
+        }
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// The arguments of a binary, read one flag at a time.
+pub struct Args {
+    args: Box<dyn Iterator<Item = String>>,
+}
+
+impl Args {
+    pub fn new(args: impl IntoIterator<Item = String> + 'static) -> Self {
+        Self {
+            args: Box::new(args.into_iter()),
+        }
+    }
+
+    /// The value following `flag`, described by `expected` when it is missing.
+    pub fn value(&mut self, flag: &str, expected: &'static str) -> Result<String, UsageError> {
+        self.args.next().ok_or_else(|| UsageError::MissingValue {
+            flag: flag.to_owned(),
+            expected,
+        })
+    }
+
+    pub fn parse<T>(&mut self, flag: &str, expected: &'static str) -> Result<T, UsageError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.value(flag, expected)?;
+        value
+            .parse()
+            .map_err(|err| UsageError::invalid(flag, &value, err))
+    }
+}
+
+impl Iterator for Args {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.args.next()
+    }
+}
+
+/// Runs `parse` on the binary's arguments. On a usage error, prints it with
+/// `usage` and exits with status 2.
+pub fn parse_args<T>(usage: &str, parse: impl FnOnce(&mut Args) -> Result<T, UsageError>) -> T {
+    parse(&mut Args::new(env::args().skip(1))).unwrap_or_else(|err| {
+        eprintln!("{err}\n\nUsage: {usage}");
+        process::exit(2)
+    })
+}
+
+pub fn print_result(part: u8, result: Result<impl fmt::Display, impl fmt::Display>) {
+    match result {
+        Ok(result) => println!("Part {part}: {result}"),
+        Err(err) => eprintln!("Part {part}: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod cli_test {
+    use crate::cli::{Args, UsageError};
+
+    #[test]
+    fn usage_errors() {
+        let mut args = Args::new(["--size", "12", "--seed", "x", "--output"].map(String::from));
+
+        assert_eq!(args.next().as_deref(), Some("--size"));
+        assert_eq!(args.parse::<u64>("--size", "a size"), Ok(12));
+
+        assert_eq!(args.next().as_deref(), Some("--seed"));
+        let err = args.parse::<u64>("--seed", "a seed").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--seed: invalid value \"x\": invalid digit found in string"
+        );
+
+        assert_eq!(args.next().as_deref(), Some("--output"));
+        assert_eq!(
+            args.value("--output", "a file path"),
+            Err(UsageError::MissingValue {
+                flag: String::from("--output"),
+                expected: "a file path"
+            })
+        );
+        assert_eq!(args.next(), None);
+    }
+}
@@ -0,0 +1,114 @@
+//! An offline driver for the fuzz targets in `fuzz/`.
+//!
+//! Each day's `fuzz` entry point runs on the cargo-fuzz corpus and crash
+//! artifacts of its target when they exist, then on random mutations of the
+//! given seed inputs. `FUZZ_SEED` and `FUZZ_RUNS` change the search.
+//! [`fuzz_test!`](crate::fuzz_test) defines a day's test in one line.
+
+use std::{
+    env, fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+};
+
+use crate::{generate::generate, rng::Rng};
+
+// Bytes that tend to matter to the parsers, plus some that never should
+const INTERESTING: &[u8] = b"0123456789 :;,|.#*-+\n\r\tGameCardTimeDistance\xff\xc3";
+
+fn mutate(rng: &mut Rng, data: &mut Vec<u8>) {
+    for _ in 0..rng.range(1..5) {
+        let at = rng.index(data.len() + 1);
+
+        match rng.range(0..7) {
+            0 if at < data.len() => data[at] = rng.range(0..256) as u8,
+            1 if at < data.len() => {
+                let end = at + rng.index((data.len() - at).min(16) + 1);
+                data.drain(at..end);
+            }
+            2 if at < data.len() => {
+                let end = at + rng.index((data.len() - at).min(64) + 1);
+                let copy = data[at..end].to_vec();
+                let to = rng.index(data.len() + 1);
+                data.splice(to..to, copy);
+            }
+            3 => {
+                let digits = vec![b'9'; rng.range(1..40) as usize];
+                data.splice(at..at, digits);
+            }
+            4 => data.truncate(at),
+            _ => data.insert(at, *rng.pick(INTERESTING)),
+        }
+    }
+}
+
+fn read_dir(path: &Path, extension: Option<&str>) -> Vec<Vec<u8>> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let matches = extension.is_none_or(|e| path.extension().is_some_and(|ext| ext == e));
+            matches.then(|| fs::read(path).ok())?
+        })
+        .collect()
+}
+
+// Reports the input that made `entry` panic before passing the panic on.
+fn run_one(target: &str, data: &[u8], entry: &impl Fn(&[u8])) {
+    if let Err(err) = panic::catch_unwind(AssertUnwindSafe(|| entry(data))) {
+        eprintln!(
+            "Fuzz target {target} panicked on input:\n\"{}\"",
+            data.escape_ascii()
+        );
+        panic::resume_unwind(err);
+    }
+}
+
+pub fn run(target: &str, seeds: &[&[u8]], entry: impl Fn(&[u8])) {
+    let var = |name: &str| env::var(name).ok().and_then(|e| e.parse().ok());
+    let runs: u64 = var("FUZZ_RUNS").unwrap_or(2000);
+    let mut rng = Rng::new(var("FUZZ_SEED").unwrap_or(0));
+
+    let fuzz_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz");
+    let mut inputs: Vec<Vec<u8>> = seeds.iter().map(|e| e.to_vec()).collect();
+    inputs.extend(read_dir(&fuzz_dir.join("corpus").join(target), None));
+    inputs.extend(read_dir(&fuzz_dir.join("artifacts").join(target), None));
+
+    for data in &inputs {
+        run_one(target, data, &entry);
+    }
+
+    for _ in 0..runs {
+        let mut data = rng.pick(&inputs).clone();
+        mutate(&mut rng, &mut data);
+        run_one(target, &data, &entry);
+    }
+}
+
+/// Runs the `day_NN` target, seeded with the day's sample cases and an
+/// input of `size` from its generator.
+pub fn run_day(day: u8, size: usize, entry: impl Fn(&[u8])) {
+    let samples = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("sample")
+        .join(format!("{day:02}"));
+    let mut seeds = read_dir(&samples, Some("txt"));
+    seeds.extend(generate(day, 0, size).map(String::into_bytes));
+
+    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+    run(&format!("day_{day:02}"), &seeds, entry);
+}
+
+/// Defines the `fuzz_never_panics` test of a day, see [`run_day`].
+#[macro_export]
+macro_rules! fuzz_test {
+    ($day:expr, $size:expr, $entry:expr) => {
+        #[test]
+        fn fuzz_never_panics() {
+            $crate::fuzz::run_day($day, $size, $entry);
+        }
+    };
+}
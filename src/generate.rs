@@ -0,0 +1,351 @@
+//! Seeded generators for puzzle inputs of any size.
+//!
+//! The meaning of `size` depends on the day:
+//!
+//! | Day | `size`                                        |
+//! |-----|-----------------------------------------------|
+//! | 01  | calibration lines                             |
+//! | 02  | games                                         |
+//! | 03  | width and height of the schematic             |
+//! | 04  | cards                                         |
+//! | 05  | seeds covered by the part 2 seed ranges       |
+//! | 06  | digits of the part 2 race time, at most 9     |
+//! | 07  | hands                                         |
+
+use std::fmt::Write;
+
+use crate::rng::Rng;
+
+pub const DAYS: [u8; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+const WORDS: [&str; 9] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const COLORS: [&str; 3] = ["red", "green", "blue"];
+const SYMBOLS: &[u8] = b"*#+$/@=%&-";
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+// Day 5 values stay below 2^32 like the real almanacs
+const ALMANAC_LIMIT: u64 = 1 << 32;
+const CARDS: &[u8] = b"23456789TJQKA";
+
+/// The size of a real input for the day.
+pub fn default_size(day: u8) -> Option<usize> {
+    match day {
+        1 => Some(1000),
+        2 => Some(100),
+        3 => Some(140),
+        4 => Some(200),
+        5 => Some(1_000_000),
+        6 => Some(8),
+        7 => Some(1000),
+        _ => None,
+    }
+}
+
+/// Generates an input for `day`, or `None` when there is no generator for it.
+pub fn generate(day: u8, seed: u64, size: usize) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+
+    match day {
+        1 => Some(day_01(rng, size)),
+        2 => Some(day_02(rng, size)),
+        3 => Some(day_03(rng, size)),
+        4 => Some(day_04(rng, size)),
+        5 => Some(day_05(rng, size)),
+        6 => Some(day_06(rng, size)),
+        7 => Some(day_07(rng, size)),
+        _ => None,
+    }
+}
+
+// Every line holds at least one digit, mixed with letters and spelled-out digits.
+pub fn day_01(rng: &mut Rng, lines: usize) -> String {
+    let mut output = String::new();
+
+    for _ in 0..lines {
+        let mut line = String::new();
+        let length = rng.range(1..40) as usize;
+
+        while line.len() < length {
+            match rng.range(0..10) {
+                0 => line.push_str(WORDS[rng.index(WORDS.len())]),
+                1 | 2 => line.push(char::from(b'1' + rng.range(0..9) as u8)),
+                _ => line.push(char::from(*rng.pick(LETTERS))),
+            }
+        }
+
+        if !line.bytes().any(|e| e.is_ascii_digit()) {
+            let at = rng.index(line.len() + 1);
+            line.insert(at, char::from(b'1' + rng.range(0..9) as u8));
+        }
+
+        output.push_str(&line);
+        output.push('\n');
+    }
+
+    output
+}
+
+pub fn day_02(rng: &mut Rng, games: usize) -> String {
+    let mut output = String::new();
+
+    for id in 1..=games {
+        let turns: Vec<String> = (0..rng.range(1..7))
+            .map(|_| {
+                let mut colors = COLORS;
+                rng.shuffle(&mut colors);
+
+                colors[..rng.range(1..4) as usize]
+                    .iter()
+                    .map(|color| format!("{} {color}", rng.range(1..21)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect();
+
+        writeln!(output, "Game {id}: {}", turns.join("; ")).unwrap();
+    }
+
+    output
+}
+
+// Numbers are always followed by a dot or a symbol, so they never run together.
+pub fn day_03(rng: &mut Rng, size: usize) -> String {
+    let mut output = String::new();
+
+    for _ in 0..size {
+        let mut row = Vec::with_capacity(size);
+
+        while row.len() < size {
+            let digits = rng.range(1..4) as usize;
+
+            match rng.range(0..20) {
+                0..=3 if row.len() + digits <= size => {
+                    row.push(b'1' + rng.range(0..9) as u8);
+                    row.extend((1..digits).map(|_| b'0' + rng.range(0..10) as u8));
+
+                    if row.len() < size {
+                        row.push(if rng.chance(1, 4) {
+                            *rng.pick(SYMBOLS)
+                        } else {
+                            b'.'
+                        });
+                    }
+                }
+                4 => row.push(*rng.pick(SYMBOLS)),
+                _ => row.push(b'.'),
+            }
+        }
+
+        output.push_str(std::str::from_utf8(&row).unwrap());
+        output.push('\n');
+    }
+
+    output
+}
+
+// Most cards have no matches, which keeps the number of copies from exploding,
+// and no card wins copies past the last card.
+pub fn day_04(rng: &mut Rng, cards: usize) -> String {
+    let mut output = String::new();
+    let width = cards.to_string().len().max(3);
+
+    for id in 1..=cards {
+        let pool = rng.sample(1..100, 35);
+        let remaining = (cards - id) as u64;
+        let matches = if remaining == 0 || rng.chance(3, 4) {
+            0
+        } else {
+            rng.range(1..remaining.min(5) + 1) as usize
+        };
+
+        let winning = &pool[..10];
+        let mut numbers: Vec<u64> = pool[..matches]
+            .iter()
+            .chain(&pool[10..35 - matches])
+            .copied()
+            .collect();
+        rng.shuffle(&mut numbers);
+
+        let join = |numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|e| format!("{e:>2}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(
+            output,
+            "Card {id:>width$}: {} | {}",
+            join(winning),
+            join(&numbers)
+        )
+        .unwrap();
+    }
+
+    output
+}
+
+// Every map moves disjoint pieces of [0, 2^32) onto each other, leaving a few
+// pieces out so they map to themselves.
+pub fn day_05(rng: &mut Rng, seeds: usize) -> String {
+    let seeds = (seeds as u64).clamp(1, ALMANAC_LIMIT);
+    let pairs = seeds.min(10);
+
+    let mut cuts: Vec<u64> = (1..pairs).map(|_| rng.range(1..seeds)).collect();
+    cuts.extend([0, seeds]);
+    cuts.sort();
+
+    let ranges: Vec<String> = cuts
+        .windows(2)
+        .filter(|e| e[1] > e[0])
+        .map(|e| {
+            let length = e[1] - e[0];
+            format!("{} {length}", rng.range(0..ALMANAC_LIMIT - length + 1))
+        })
+        .collect();
+
+    let mut output = format!("seeds: {}\n", ranges.join(" "));
+
+    for names in CATEGORIES.windows(2) {
+        let mut cuts: Vec<u64> = (0..rng.range(10..40))
+            .map(|_| rng.range(1..ALMANAC_LIMIT))
+            .collect();
+        cuts.extend([0, ALMANAC_LIMIT]);
+        cuts.sort();
+        cuts.dedup();
+
+        let pieces: Vec<(u64, u64)> = cuts.windows(2).map(|e| (e[0], e[1] - e[0])).collect();
+        let mut order: Vec<usize> = (0..pieces.len()).collect();
+        rng.shuffle(&mut order);
+
+        let mut dest = 0;
+        let mut lines = Vec::new();
+
+        for i in order {
+            let (source, length) = pieces[i];
+
+            if !rng.chance(1, 5) {
+                lines.push(format!("{dest} {source} {length}"));
+            }
+
+            dest += length;
+        }
+
+        rng.shuffle(&mut lines);
+
+        write!(output, "\n{}-to-{} map:\n", names[0], names[1]).unwrap();
+
+        for line in lines {
+            writeln!(output, "{line}").unwrap();
+        }
+    }
+
+    output
+}
+
+// A race of `d` digits lasts at least 3 * 10^(d - 1) and has a record of
+// 2d - 1 digits, so the record can be beaten both per race and once the
+// columns are joined for part 2.
+pub fn day_06(rng: &mut Rng, digits: usize) -> String {
+    let mut digits = digits.clamp(1, 9) as u32;
+    let mut races = Vec::new();
+
+    while digits > 0 {
+        let d = rng.range(1..digits.min(3) as u64 + 1) as u32;
+        let time = rng.range(3 * 10u64.pow(d - 1)..10u64.pow(d));
+        let best = (time / 2) * (time - time / 2);
+
+        races.push((time, rng.range(10u64.pow(2 * d - 2)..best)));
+        digits -= d;
+    }
+
+    let mut times = String::from("Time:    ");
+    let mut distances = String::from("Distance:");
+
+    for (time, record) in races {
+        let width = record.to_string().len() + 3;
+        write!(times, "{time:>width$}").unwrap();
+        write!(distances, "{record:>width$}").unwrap();
+    }
+
+    format!("{times}\n{distances}\n")
+}
+
+// Cards often repeat within a hand, so whole hands repeat too, each time with
+// its own bid. Day 7 has to rank those identical hands in input order.
+pub fn day_07(rng: &mut Rng, hands: usize) -> String {
+    let mut output = String::new();
+
+    for _ in 0..hands {
+        let mut hand = [0; 5];
+
+        for i in 0..hand.len() {
+            hand[i] = if i > 0 && rng.chance(2, 5) {
+                hand[rng.index(i)]
+            } else {
+                *rng.pick(CARDS)
+            };
+        }
+
+        let bid = rng.range(1..1001);
+        writeln!(output, "{} {bid}", std::str::from_utf8(&hand).unwrap()).unwrap();
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod generate_test {
+    use crate::generate::{default_size, generate, DAYS};
+
+    #[test]
+    fn reproducible() {
+        for day in DAYS {
+            let size = default_size(day).unwrap().min(200);
+
+            assert_eq!(generate(day, 42, size), generate(day, 42, size));
+            assert_ne!(generate(day, 42, size), generate(day, 43, size));
+        }
+
+        assert_eq!(generate(8, 42, 10), None);
+    }
+
+    #[test]
+    fn shapes() {
+        let input = generate(3, 1, 50).unwrap();
+        assert_eq!(input.lines().count(), 50);
+        assert!(input.lines().all(|e| e.len() == 50));
+
+        let input = generate(4, 1, 30).unwrap();
+        assert_eq!(input.lines().count(), 30);
+        assert!(input
+            .lines()
+            .all(|e| e.split_whitespace().count() == 2 + 10 + 1 + 25));
+
+        let input = generate(5, 1, 1000).unwrap();
+        let seeds: Vec<u64> = input.lines().next().unwrap()[7..]
+            .split_whitespace()
+            .map(|e| e.parse().unwrap())
+            .collect();
+        assert_eq!(seeds.iter().skip(1).step_by(2).sum::<u64>(), 1000);
+        assert_eq!(input.matches("map:").count(), 7);
+
+        let input = generate(6, 1, 8).unwrap();
+        let time: String = input.lines().next().unwrap()[5..]
+            .split_whitespace()
+            .collect();
+        assert_eq!(time.len(), 8);
+    }
+}
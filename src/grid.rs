@@ -0,0 +1,273 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Debug)]
+pub struct Location {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Location {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+}
+
+const NEIGHBOURS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const NEIGHBOURS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum GridError {
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row} has {found} cells, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A rectangular grid stored row by row, indexed by [`Location`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows<R>(rows: impl IntoIterator<Item = R>) -> Result<Self, GridError>
+    where
+        R: IntoIterator<Item = T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+
+        for row in rows {
+            let start = cells.len();
+            cells.extend(row);
+            let found = cells.len() - start;
+
+            match width {
+                None => width = Some(found),
+                Some(expected) if expected != found => {
+                    return Err(GridError::RaggedRow {
+                        row: height,
+                        expected,
+                        found,
+                    })
+                }
+                Some(_) => {}
+            }
+
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    /// Builds a grid as wide as the longest row, padding shorter rows with
+    /// `fill`.
+    pub fn from_rows_padded<R>(rows: impl IntoIterator<Item = R>, fill: T) -> Self
+    where
+        R: IntoIterator<Item = T>,
+        T: Clone,
+    {
+        let rows: Vec<Vec<T>> = rows
+            .into_iter()
+            .map(|row| row.into_iter().collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * rows.len());
+
+        for mut row in rows {
+            row.resize(width, fill.clone());
+            cells.extend(row);
+        }
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, location: Location) -> bool {
+        location.x < self.width && location.y < self.height
+    }
+
+    pub fn get(&self, location: Location) -> Option<&T> {
+        self.contains(location)
+            .then(|| &self.cells[location.y * self.width + location.x])
+    }
+
+    pub fn get_mut(&mut self, location: Location) -> Option<&mut T> {
+        if self.contains(location) {
+            Some(&mut self.cells[location.y * self.width + location.x])
+        } else {
+            None
+        }
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T>> {
+        (x < self.width).then(|| self.cells.iter().skip(x).step_by(self.width))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn locations(&self) -> impl Iterator<Item = Location> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Location::new(x, y)))
+    }
+
+    /// Moves `location` by the given offsets, or returns `None` when the
+    /// result falls outside the grid.
+    pub fn offset(&self, location: Location, dx: isize, dy: isize) -> Option<Location> {
+        let x = location.x.checked_add_signed(dx)?;
+        let y = location.y.checked_add_signed(dy)?;
+
+        self.contains(Location::new(x, y))
+            .then_some(Location::new(x, y))
+    }
+
+    /// The up to four orthogonally adjacent locations inside the grid.
+    pub fn neighbours_4(&self, location: Location) -> impl Iterator<Item = Location> + '_ {
+        NEIGHBOURS_4
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(location, dx, dy))
+    }
+
+    /// The up to eight adjacent locations inside the grid, diagonals included.
+    pub fn neighbours_8(&self, location: Location) -> impl Iterator<Item = Location> + '_ {
+        NEIGHBOURS_8
+            .iter()
+            .filter_map(move |&(dx, dy)| self.offset(location, dx, dy))
+    }
+}
+
+impl Grid<char> {
+    pub fn from_text(input: &str) -> Result<Self, GridError> {
+        Self::from_rows(input.lines().map(str::chars))
+    }
+}
+
+impl<T> Index<Location> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, location: Location) -> &Self::Output {
+        self.get(location)
+            .expect("Location should be inside the grid")
+    }
+}
+
+impl<T> IndexMut<Location> for Grid<T> {
+    fn index_mut(&mut self, location: Location) -> &mut Self::Output {
+        self.get_mut(location)
+            .expect("Location should be inside the grid")
+    }
+}
+
+#[cfg(test)]
+mod grid_test {
+    use crate::grid::{Grid, GridError, Location};
+
+    #[test]
+    fn indexing_and_views() {
+        let grid = Grid::from_text("abc\ndef").unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Location::new(2, 1)], 'f');
+        assert_eq!(grid.get(Location::new(3, 0)), None);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.column(1).unwrap().copied().collect::<String>(), "be");
+        assert!(grid.column(3).is_none());
+
+        let grid = Grid::from_rows_padded(["ab", "", "abc"].iter().map(|e| e.chars()), '.');
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.row(1), Some(&['.', '.', '.'][..]));
+
+        // Empty rows still count, as in `from_rows`
+        let grid = Grid::from_rows_padded(["", ""].iter().map(|e| e.chars()), '.');
+        assert_eq!((grid.width(), grid.height()), (0, 2));
+        assert_eq!(grid.row(1), Some(&[][..]));
+        assert_eq!(grid, Grid::from_text("\n\n").unwrap());
+
+        let result = Grid::from_text("abc\nde");
+        assert_eq!(
+            result,
+            Err(GridError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::from_text("abc\ndef\nghi").unwrap();
+        let collect =
+            |it: &mut dyn Iterator<Item = Location>| it.map(|e| grid[e]).collect::<String>();
+
+        assert_eq!(collect(&mut grid.neighbours_4(Location::new(1, 1))), "bdfh");
+        assert_eq!(
+            collect(&mut grid.neighbours_8(Location::new(1, 1))),
+            "abcdfghi"
+        );
+        assert_eq!(collect(&mut grid.neighbours_8(Location::new(0, 0))), "bde");
+        assert_eq!(collect(&mut grid.neighbours_4(Location::new(2, 2))), "fh");
+    }
+}
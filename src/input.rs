@@ -0,0 +1,115 @@
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn data_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+/// Where `./new` downloads the puzzle input for `day`.
+pub fn input_path(day: u8) -> PathBuf {
+    data_dir().join("input").join(format!("{day:02}.txt"))
+}
+
+/// Where the sample case `name` of `day` lives, see `build.rs`.
+pub fn sample_path(day: u8, name: &str) -> PathBuf {
+    data_dir()
+        .join("sample")
+        .join(format!("{day:02}"))
+        .join(format!("{name}.txt"))
+}
+
+pub fn read_input(path: &Path) -> String {
+    fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Could not read {}: {err}", path.display()))
+}
+
+/// An input together with the answers recorded for it, each part's answer in
+/// a `.part1` or `.part2` file next to the input.
+pub struct Case {
+    pub name: String,
+    pub input: String,
+    answers: [Option<String>; 2],
+}
+
+impl Case {
+    pub fn load(path: &Path) -> Option<Self> {
+        let input = fs::read_to_string(path).ok()?;
+        let answers = [1, 2].map(|part| {
+            fs::read_to_string(path.with_extension(format!("part{part}")))
+                .ok()
+                .map(|e| e.trim().to_owned())
+        });
+
+        Some(Self {
+            name: path.display().to_string(),
+            input,
+            answers,
+        })
+    }
+
+    /// The puzzle input for `day`, or `None` when it has not been downloaded.
+    pub fn golden(day: u8) -> Option<Self> {
+        let path = input_path(day);
+        let case = Self::load(&path);
+
+        if case.is_none() {
+            eprintln!("Skipping golden test, {} not found", path.display());
+        }
+
+        case
+    }
+
+    /// A sample case, which must record an answer for at least one part.
+    pub fn sample(day: u8, name: &str) -> Self {
+        let path = sample_path(day, name);
+        let case = Self::load(&path).unwrap_or_else(|| panic!("Could not read {}", path.display()));
+
+        assert!(
+            case.answers.iter().any(Option::is_some),
+            "{} has no recorded answers",
+            case.name
+        );
+
+        case
+    }
+
+    /// Panics when `solve` gives another answer than the one recorded for
+    /// `part`. Parts without a recorded answer are not solved, so a case can
+    /// hold an input that only one part accepts.
+    pub fn check<T: Display>(&self, part: u8, solve: impl FnOnce() -> T) {
+        if let Some(expected) = &self.answers[usize::from(part) - 1] {
+            assert_eq!(
+                &solve().to_string(),
+                expected,
+                "Part {part} of {}",
+                self.name
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod input_test {
+    use crate::input::{input_path, sample_path, Case};
+    use std::path::Path;
+
+    #[test]
+    fn unrecorded_answers_are_not_checked() {
+        assert!(input_path(5).ends_with("data/input/05.txt"));
+        assert!(sample_path(3, "edges").ends_with("data/sample/03/edges.txt"));
+        assert!(Case::load(Path::new("missing.txt")).is_none());
+
+        let case = Case::sample(1, "sample_1");
+        case.check(1, || 142);
+        case.check(2, || -> u64 { panic!("Part 2 has no recorded answer") });
+    }
+
+    #[test]
+    #[should_panic(expected = "Part 1 of")]
+    fn wrong_answer() {
+        Case::sample(5, "sample").check(1, || 36);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod cli;
+pub mod fuzz;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod prop;
+pub mod rng;
@@ -0,0 +1,210 @@
+use std::{env, fmt::Debug};
+
+use crate::rng::Rng;
+
+/// A value that can propose smaller versions of itself, simplest first.
+pub trait Shrink: Clone + Debug {
+    fn shrink(&self) -> Vec<Self>;
+}
+
+macro_rules! impl_shrink_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Shrink for $t {
+                // Towards zero: zero itself, then halving, then one less
+                fn shrink(&self) -> Vec<Self> {
+                    let x = *self;
+                    let mut candidates = Vec::new();
+
+                    if x > 0 {
+                        candidates.push(0);
+                    }
+
+                    if x / 2 > 0 {
+                        candidates.push(x / 2);
+                    }
+
+                    if x > 1 && x - 1 > x / 2 {
+                        candidates.push(x - 1);
+                    }
+
+                    candidates
+                }
+            }
+        )*
+    };
+}
+
+impl_shrink_unsigned!(u8, u32, u64, usize);
+
+impl<T: Shrink> Shrink for Vec<T> {
+    // Drops ever smaller chunks of elements before shrinking single elements.
+    fn shrink(&self) -> Vec<Self> {
+        let mut candidates = Vec::new();
+
+        if !self.is_empty() {
+            candidates.push(Vec::new());
+        }
+
+        let mut size = self.len() / 2;
+
+        while size > 0 {
+            for start in (0..self.len()).step_by(size) {
+                let mut candidate = self.clone();
+                candidate.drain(start..(start + size).min(self.len()));
+                candidates.push(candidate);
+            }
+
+            size /= 2;
+        }
+
+        for (i, e) in self.iter().enumerate() {
+            for smaller in e.shrink() {
+                let mut candidate = self.clone();
+                candidate[i] = smaller;
+                candidates.push(candidate);
+            }
+        }
+
+        candidates
+    }
+}
+
+impl<A: Shrink, B: Shrink> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b) = self;
+
+        a.shrink()
+            .into_iter()
+            .map(|a| (a, b.clone()))
+            .chain(b.shrink().into_iter().map(|b| (a.clone(), b)))
+            .collect()
+    }
+}
+
+impl<A: Shrink, B: Shrink, C: Shrink> Shrink for (A, B, C) {
+    fn shrink(&self) -> Vec<Self> {
+        let (a, b, c) = self;
+
+        ((a.clone(), b.clone()), c.clone())
+            .shrink()
+            .into_iter()
+            .map(|((a, b), c)| (a, b, c))
+            .collect()
+    }
+}
+
+pub struct Config {
+    pub seed: u64,
+    pub cases: usize,
+}
+
+impl Default for Config {
+    // `PROP_SEED` and `PROP_CASES` override the fixed defaults, e.g. to
+    // replay a failure or to run a longer search.
+    fn default() -> Self {
+        let var = |name: &str| env::var(name).ok().and_then(|e| e.parse().ok());
+
+        Self {
+            seed: var("PROP_SEED").unwrap_or(0),
+            cases: var("PROP_CASES").map_or(200, |e: u64| e as usize),
+        }
+    }
+}
+
+/// Succeeds when the naive and the optimized results agree.
+pub fn agree<T: PartialEq + Debug>(naive: T, fast: T) -> Result<(), String> {
+    if naive == fast {
+        Ok(())
+    } else {
+        Err(format!("naive: {naive:?}\nfast:  {fast:?}"))
+    }
+}
+
+/// Shrinks a failing value until none of its candidates fail any more, and
+/// returns it with its failure and the number of shrinking steps.
+pub fn shrink<T: Shrink>(
+    mut value: T,
+    mut message: String,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> (T, String, usize) {
+    let mut steps = 0;
+
+    'outer: loop {
+        for candidate in value.shrink() {
+            if let Err(failure) = property(&candidate) {
+                (value, message) = (candidate, failure);
+                steps += 1;
+                continue 'outer;
+            }
+        }
+
+        return (value, message, steps);
+    }
+}
+
+pub fn check<T: Shrink>(
+    name: &str,
+    generate: impl FnMut(&mut Rng) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    check_with(Config::default(), name, generate, property);
+}
+
+/// Runs `property` on `config.cases` generated values and panics with the
+/// smallest counterexample found.
+pub fn check_with<T: Shrink>(
+    config: Config,
+    name: &str,
+    mut generate: impl FnMut(&mut Rng) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    let mut rng = Rng::new(config.seed);
+
+    for case in 0..config.cases {
+        let value = generate(&mut rng);
+
+        if let Err(message) = property(&value) {
+            let (value, message, steps) = shrink(value, message, &property);
+
+            panic!(
+                "Property {name} failed on case {case} (PROP_SEED={}), \
+                 shrunk in {steps} steps to:\n{value:?}\n{message}",
+                config.seed
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod prop_test {
+    use crate::prop::{agree, check, shrink, Shrink};
+
+    #[test]
+    fn shrinking() {
+        assert_eq!(10_u64.shrink(), vec![0, 5, 9]);
+        assert!(0_u64.shrink().is_empty());
+
+        let (value, _, _) = shrink(1000_u64, String::new(), |&x| agree(x < 37, true));
+        assert_eq!(value, 37);
+
+        let failing = vec![3_u64, 80, 5, 12, 9];
+        let (value, message, _) = shrink(failing, String::new(), |v| {
+            agree(v.iter().sum::<u64>() < 20, true)
+        });
+        assert_eq!(value.iter().sum::<u64>(), 20);
+        assert!(value.len() <= 2);
+        assert_eq!(message, "naive: false\nfast:  true");
+
+        let (value, _, _) = shrink((50_u64, 50_u64), String::new(), |&(a, b)| {
+            agree(a + b < 30 || a < b, true)
+        });
+        assert_eq!(value, (30, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "shrunk in")]
+    fn reports_counterexample() {
+        check("even", |rng| rng.range(0..100), |x| agree(x % 2, 0));
+    }
+}
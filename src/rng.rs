@@ -0,0 +1,89 @@
+use std::ops::Range;
+
+/// A small SplitMix64 generator, so generated inputs only depend on the seed.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "Range should not be empty");
+
+        let span = range.end - range.start;
+        // Widening multiply instead of a modulo to avoid its bias
+        range.start + ((self.next_u64() as u128 * span as u128) >> 64) as u64
+    }
+
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    /// `true` with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.range(0..denominator) < numerator
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// `count` distinct values from `range`, in random order.
+    pub fn sample(&mut self, range: Range<u64>, count: usize) -> Vec<u64> {
+        let mut values: Vec<u64> = range.collect();
+        assert!(count <= values.len(), "Range should hold enough values");
+
+        for i in 0..count {
+            let j = i + self.index(values.len() - i);
+            values.swap(i, j);
+        }
+
+        values.truncate(count);
+        values
+    }
+}
+
+#[cfg(test)]
+mod rng_test {
+    use crate::rng::Rng;
+
+    #[test]
+    fn reproducible_and_bounded() {
+        let first: Vec<u64> = (0..5)
+            .scan(Rng::new(7), |rng, _| Some(rng.next_u64()))
+            .collect();
+        let second: Vec<u64> = (0..5)
+            .scan(Rng::new(7), |rng, _| Some(rng.next_u64()))
+            .collect();
+        assert_eq!(first, second);
+
+        let mut rng = Rng::new(1);
+        assert!((0..1000).all(|_| (10..20).contains(&rng.range(10..20))));
+
+        let mut sample = rng.sample(1..100, 25);
+        assert_eq!(sample.len(), 25);
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 25);
+    }
+}